@@ -7,6 +7,7 @@ import (
 	"os"
 	"os/signal"
 	"syscall"
+	"time"
 	"zrb/internal/backup"
 	"zrb/internal/check"
 	"zrb/internal/keys"
@@ -32,9 +33,17 @@ func main() {
 						Usage: "path to configuration yaml file",
 						Value: "zrb_config.yaml",
 					},
+					&cli.StringFlag{
+						Name:  "max-age",
+						Usage: "Fail if an enabled task has no backup of any level newer than this (e.g. 36h), or per level (e.g. 0=35d,1=8d,2=2d)",
+					},
 				},
 				Action: func(ctx context.Context, cmd *cli.Command) error {
-					return check.Run(ctx, cmd.String("config"))
+					maxAge, err := check.ParseMaxAge(cmd.String("max-age"))
+					if err != nil {
+						return err
+					}
+					return check.Run(ctx, cmd.String("config"), maxAge)
 				},
 			},
 			{
@@ -54,9 +63,8 @@ func main() {
 						Value: "zrb_config.yaml",
 					},
 					&cli.StringFlag{
-						Name:     "private-key",
-						Usage:    "Path to age private key file",
-						Required: true,
+						Name:  "private-key",
+						Usage: "Path to age private key file (default: key from $ZRB_AGE_PRIVATE_KEY)",
 					},
 				},
 				Action: func(ctx context.Context, cmd *cli.Command) error {
@@ -73,18 +81,99 @@ func main() {
 						Value: "zrb_config.yaml",
 					},
 					&cli.StringFlag{
-						Name:     "task",
-						Usage:    "Name of the backup task to run.",
-						Required: true,
+						Name:  "task",
+						Usage: "Name of the backup task to run.",
+					},
+					&cli.BoolFlag{
+						Name:  "all",
+						Usage: "Run all enabled tasks, continuing past failures",
+						Value: false,
 					},
 					&cli.Int16Flag{
 						Name:     "level",
 						Usage:    "Backup level to perform.",
 						Required: true,
 					},
+					&cli.BoolFlag{
+						Name:  "dry-run",
+						Usage: "Show what would be backed up without making changes",
+						Value: false,
+					},
+					&cli.BoolFlag{
+						Name:  "force",
+						Usage: "Overwrite a completed backup of the same level and date",
+						Value: false,
+					},
+					&cli.BoolFlag{
+						Name:  "skip-verify",
+						Usage: "Skip checking uploaded level 0 parts against their size and BLAKE3 hash",
+						Value: false,
+					},
+					&cli.BoolFlag{
+						Name:  "keep-local",
+						Usage: "Keep the local encrypted parts and manifest after a successful upload",
+						Value: false,
+					},
+				},
+				Action: func(ctx context.Context, cmd *cli.Command) error {
+					if cmd.Bool("all") == (cmd.String("task") != "") {
+						return fmt.Errorf("exactly one of --task or --all is required")
+					}
+					if cmd.Bool("all") {
+						if cmd.Bool("dry-run") {
+							return fmt.Errorf("--dry-run requires --task")
+						}
+						return backup.RunAll(ctx, cmd.String("config"), cmd.Int16("level"),
+							cmd.Bool("force"), cmd.Bool("skip-verify"), cmd.Bool("keep-local"))
+					}
+					if cmd.Bool("dry-run") {
+						return backup.DryRun(ctx, cmd.String("config"), cmd.Int16("level"), cmd.String("task"))
+					}
+					return backup.Run(ctx, cmd.String("config"), cmd.Int16("level"), cmd.String("task"),
+						cmd.Bool("force"), cmd.Bool("skip-verify"), cmd.Bool("keep-local"))
+				},
+			},
+			{
+				Name:  "abort",
+				Usage: "Discard the in-progress backup state of a task",
+				Flags: []cli.Flag{
+					&cli.StringFlag{
+						Name:  "config",
+						Usage: "path to configuration yaml file",
+						Value: "zrb_config.yaml",
+					},
+					&cli.StringFlag{
+						Name:     "task",
+						Usage:    "Name of the backup task",
+						Required: true,
+					},
+					&cli.BoolFlag{
+						Name:  "keep-files",
+						Usage: "Keep the local and uploaded part files of the aborted backup",
+						Value: false,
+					},
+				},
+				Action: func(ctx context.Context, cmd *cli.Command) error {
+					return backup.Abort(ctx, cmd.String("config"), cmd.String("task"), cmd.Bool("keep-files"))
+				},
+			},
+			{
+				Name:  "status",
+				Usage: "Show where an interrupted backup of a task would resume",
+				Flags: []cli.Flag{
+					&cli.StringFlag{
+						Name:  "config",
+						Usage: "path to configuration yaml file",
+						Value: "zrb_config.yaml",
+					},
+					&cli.StringFlag{
+						Name:     "task",
+						Usage:    "Name of the backup task",
+						Required: true,
+					},
 				},
 				Action: func(ctx context.Context, cmd *cli.Command) error {
-					return backup.Run(ctx, cmd.String("config"), cmd.Int16("level"), cmd.String("task"))
+					return backup.Status(ctx, cmd.String("config"), cmd.String("task"))
 				},
 			},
 			{
@@ -101,14 +190,25 @@ func main() {
 						Usage:    "ZFS dataset name",
 						Required: true,
 					},
+					&cli.Int16Flag{
+						Name:  "level",
+						Usage: "Backup level the snapshot is taken for (named zrb_level<N>_<date>)",
+						Value: 0,
+					},
 					&cli.StringFlag{
 						Name:  "prefix",
-						Usage: "Snapshot name prefix",
-						Value: "zrb_level0",
+						Usage: "Deprecated: use --level. Snapshot name prefix (named <prefix>_<date>)",
 					},
 				},
 				Action: func(ctx context.Context, cmd *cli.Command) error {
-					return zfs.CreateSnapshot(cmd.String("pool"), cmd.String("dataset"), cmd.String("prefix"))
+					if cmd.IsSet("prefix") {
+						if cmd.IsSet("level") {
+							return fmt.Errorf("--prefix and --level cannot be used together")
+						}
+						slog.Warn("--prefix is deprecated, use --level instead")
+						return zfs.CreateSnapshotWithPrefix(cmd.String("pool"), cmd.String("dataset"), cmd.String("prefix"))
+					}
+					return zfs.CreateSnapshot(cmd.String("pool"), cmd.String("dataset"), cmd.Int16("level"))
 				},
 			},
 			{
@@ -132,7 +232,7 @@ func main() {
 					},
 					&cli.StringFlag{
 						Name:  "source",
-						Usage: "Data source: local or s3",
+						Usage: "Data source: local, s3, or history (every completed backup, from the local history file)",
 						Value: "local",
 					},
 				},
@@ -165,9 +265,8 @@ func main() {
 						Required: true,
 					},
 					&cli.StringFlag{
-						Name:     "private-key",
-						Usage:    "Path to age private key file",
-						Required: true,
+						Name:  "private-key",
+						Usage: "Path to age private key file (default: key from $ZRB_AGE_PRIVATE_KEY)",
 					},
 					&cli.StringFlag{
 						Name:  "source",
@@ -184,11 +283,45 @@ func main() {
 						Usage: "Pass -F to zfs receive, discarding uncommitted changes in the target dataset",
 						Value: false,
 					},
+					&cli.BoolFlag{
+						Name:  "chain",
+						Usage: "Also restore every lower-level backup this level is based on, starting from level 0",
+						Value: false,
+					},
 				},
 				Action: func(ctx context.Context, cmd *cli.Command) error {
 					return restore.Run(ctx, cmd.String("config"), cmd.String("task"),
 						cmd.Int16("level"), cmd.String("target"), cmd.String("private-key"),
-						cmd.String("source"), cmd.Bool("dry-run"), cmd.Bool("force"))
+						cmd.String("source"), cmd.Bool("dry-run"), cmd.Bool("force"), cmd.Bool("chain"))
+				},
+			},
+			{
+				Name:  "presign",
+				Usage: "Print time-limited download URLs for the latest backup of a level",
+				Flags: []cli.Flag{
+					&cli.StringFlag{
+						Name:  "config",
+						Usage: "path to configuration yaml file",
+						Value: "zrb_config.yaml",
+					},
+					&cli.StringFlag{
+						Name:     "task",
+						Usage:    "Name of the backup task",
+						Required: true,
+					},
+					&cli.Int16Flag{
+						Name:     "level",
+						Usage:    "Backup level to share",
+						Required: true,
+					},
+					&cli.DurationFlag{
+						Name:  "expires",
+						Usage: "How long the URLs stay valid (at most 168h)",
+						Value: 24 * time.Hour,
+					},
+				},
+				Action: func(ctx context.Context, cmd *cli.Command) error {
+					return restore.Presign(ctx, cmd.String("config"), cmd.String("task"), cmd.Int16("level"), cmd.Duration("expires"))
 				},
 			},
 		},
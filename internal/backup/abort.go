@@ -0,0 +1,109 @@
+package backup
+
+import (
+	"context"
+	"fmt"
+	"log/slog"
+	"os"
+	"path/filepath"
+	"sort"
+	"zrb/internal/config"
+	"zrb/internal/lock"
+	"zrb/internal/manifest"
+	"zrb/internal/remote"
+	"zrb/internal/util"
+)
+
+// Abort discards the in-progress backup state of a task so the next run starts fresh.
+// Unless keepFiles is set, the local output directory and any parts already uploaded
+// for the abandoned backup are removed too.
+func Abort(ctx context.Context, configPath, taskName string, keepFiles bool) error {
+	cfg, err := config.Load(configPath)
+	if err != nil {
+		return fmt.Errorf("failed to load config: %w", err)
+	}
+
+	task, err := cfg.FindTask(taskName)
+	if err != nil {
+		return err
+	}
+
+	runDir := util.RunDir(cfg.BaseDir, task.Pool, task.Dataset)
+	releaseLock, err := lock.Acquire(filepath.Join(runDir, "zrb.lock"))
+	if err != nil {
+		return fmt.Errorf("failed to acquire lock: %w", err)
+	}
+	defer func() {
+		if err := releaseLock(); err != nil {
+			slog.Warn("Failed to release lock", "error", err)
+		}
+	}()
+
+	statePath := filepath.Join(runDir, "backup_state.yaml")
+	state, err := manifest.ReadState(statePath)
+	if err != nil {
+		if os.IsNotExist(err) {
+			return fmt.Errorf("no backup in progress for task %s", taskName)
+		}
+		return fmt.Errorf("failed to read backup state: %w", err)
+	}
+
+	slog.Info("Aborting backup", "task", taskName, "level", state.BackupLevel, "snapshot", state.TargetSnapshot,
+		"partsCompleted", len(state.PartsCompleted))
+
+	if !keepFiles && cfg.S3.Enabled && state.OutputDir != "" {
+		backend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region, cfg.S3.Prefix, cfg.S3.Endpoint,
+			cfg.S3.StorageClass.Manifest, cfg.S3RetryAttempts())
+		if err != nil {
+			return fmt.Errorf("failed to initialize S3 backend: %w", err)
+		}
+
+		taskDirName := filepath.Join(filepath.Base(filepath.Dir(state.OutputDir)), filepath.Base(state.OutputDir))
+		if err := deleteUploadedParts(ctx, backend, state.ManifestUploaded, task, taskDirName); err != nil {
+			return err
+		}
+	}
+
+	if !keepFiles && state.OutputDir != "" {
+		slog.Info("Removing local backup files", "path", state.OutputDir)
+		if err := os.RemoveAll(state.OutputDir); err != nil {
+			return fmt.Errorf("failed to remove output directory: %w", err)
+		}
+	}
+
+	if err := os.Remove(statePath); err != nil {
+		return fmt.Errorf("failed to remove backup state: %w", err)
+	}
+
+	slog.Info("Backup aborted")
+	return nil
+}
+
+// deleteUploadedParts removes the parts, and with withManifest the task manifest, uploaded for a backup.
+// Parts are listed rather than taken from the state, which misses a part uploaded just before a crash.
+// Deleting is idempotent, so a failed abort can simply be re-run.
+func deleteUploadedParts(ctx context.Context, backend remote.Backend, withManifest bool, task *config.Task, taskDirName string) error {
+	dataDir := filepath.Join("data", task.Pool, task.Dataset, taskDirName)
+	objects, err := backend.List(ctx, dataDir)
+	if err != nil {
+		return fmt.Errorf("failed to list uploaded parts: %w", err)
+	}
+
+	var remotePaths []string
+	for _, remotePath := range objects {
+		if matched, _ := filepath.Match("snapshot.part-*.age", filepath.Base(remotePath)); matched {
+			remotePaths = append(remotePaths, remotePath)
+		}
+	}
+	sort.Strings(remotePaths)
+	slog.Info("Deleting uploaded parts", "count", len(remotePaths))
+
+	if withManifest {
+		remotePaths = append(remotePaths, filepath.Join("manifests", task.Pool, task.Dataset, taskDirName, "task_manifest.yaml"))
+	}
+
+	if err := backend.DeleteAll(ctx, remotePaths); err != nil {
+		return fmt.Errorf("failed to delete uploaded files: %w", err)
+	}
+	return nil
+}
@@ -0,0 +1,167 @@
+package backup
+
+import (
+	"context"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"zrb/internal/config"
+	"zrb/internal/manifest"
+	"zrb/internal/remote"
+
+	"github.com/aws/aws-sdk-go-v2/service/s3/types"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func setupAbort(t *testing.T) (configPath, statePath, outputDir string) {
+	t.Helper()
+
+	baseDir := t.TempDir()
+	configPath = filepath.Join(baseDir, "zrb_config.yaml")
+	cfg := fmt.Sprintf(`base_dir: %s
+age_public_key: age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p
+tasks:
+  - name: t1
+    pool: tank
+    dataset: data
+    enabled: true
+`, baseDir)
+	require.NoError(t, os.WriteFile(configPath, []byte(cfg), 0o644))
+
+	runDir := filepath.Join(baseDir, "run", "tank", "data")
+	require.NoError(t, os.MkdirAll(runDir, 0o755))
+
+	outputDir = filepath.Join(baseDir, "task", "tank", "data", "level0", "20240101")
+	require.NoError(t, os.MkdirAll(outputDir, 0o755))
+	require.NoError(t, os.WriteFile(filepath.Join(outputDir, "snapshot.part-aaaaaa.age"), []byte("data"), 0o644))
+
+	statePath = filepath.Join(runDir, "backup_state.yaml")
+	state := &manifest.State{
+		TaskName:       "t1",
+		TargetSnapshot: "tank/data@zrb_level0_2024-01-01_00-00",
+		OutputDir:      outputDir,
+		PartsCompleted: map[string]manifest.PartInfo{},
+	}
+	require.NoError(t, manifest.WriteState(statePath, state))
+
+	return configPath, statePath, outputDir
+}
+
+func TestAbort(t *testing.T) {
+	t.Run("removes state and files", func(t *testing.T) {
+		configPath, statePath, outputDir := setupAbort(t)
+
+		require.NoError(t, Abort(context.Background(), configPath, "t1", false))
+		assert.NoFileExists(t, statePath)
+		assert.NoDirExists(t, outputDir)
+	})
+
+	t.Run("keeps files", func(t *testing.T) {
+		configPath, statePath, outputDir := setupAbort(t)
+
+		require.NoError(t, Abort(context.Background(), configPath, "t1", true))
+		assert.NoFileExists(t, statePath)
+		assert.DirExists(t, outputDir)
+	})
+
+	t.Run("no backup in progress", func(t *testing.T) {
+		configPath, statePath, _ := setupAbort(t)
+		require.NoError(t, os.Remove(statePath))
+
+		assert.ErrorContains(t, Abort(context.Background(), configPath, "t1", false), "no backup in progress")
+	})
+}
+
+type fakeBackend struct {
+	objects  map[string]bool
+	infos    map[string]*remote.ObjectInfo
+	uploaded []string
+	deleted  []string
+}
+
+func (f *fakeBackend) Upload(_ context.Context, _, remotePath, _ string, _ int16) error {
+	f.uploaded = append(f.uploaded, remotePath)
+	return nil
+}
+
+func (f *fakeBackend) Head(_ context.Context, remotePath string) (*remote.ObjectInfo, error) {
+	if info, ok := f.infos[remotePath]; ok {
+		return info, nil
+	}
+	if !f.objects[remotePath] {
+		return nil, fmt.Errorf("failed to head object %s: %w", remotePath, &types.NotFound{})
+	}
+	return &remote.ObjectInfo{}, nil
+}
+
+func (f *fakeBackend) List(_ context.Context, remoteDir string) ([]string, error) {
+	var remotePaths []string
+	for remotePath := range f.objects {
+		if strings.HasPrefix(remotePath, remoteDir+"/") {
+			remotePaths = append(remotePaths, remotePath)
+		}
+	}
+	return remotePaths, nil
+}
+
+func (f *fakeBackend) DeleteAll(_ context.Context, remotePaths []string) error {
+	f.deleted = append(f.deleted, remotePaths...)
+	return nil
+}
+
+func (f *fakeBackend) VerifyCredentials(context.Context) error { return nil }
+
+func TestDeleteUploadedParts(t *testing.T) {
+	task := &config.Task{Name: "t1", Pool: "tank", Dataset: "data"}
+	state := &manifest.State{
+		PartsCompleted: map[string]manifest.PartInfo{
+			"aaaaaa": {Index: "aaaaaa"},
+			"aaaaab": {Index: "aaaaab"},
+		},
+		ManifestUploaded: true,
+	}
+
+	backend := &fakeBackend{objects: map[string]bool{
+		"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age": true,
+		"data/tank/data/level0/20240101/snapshot.part-aaaaab.age": true,
+		// uploaded just before a crash, so not recorded in the state
+		"data/tank/data/level0/20240101/snapshot.part-aaaaac.age": true,
+		"data/tank/data/level0/20240102/snapshot.part-aaaaaa.age": true,
+	}}
+	require.NoError(t, deleteUploadedParts(context.Background(), backend, state.ManifestUploaded, task, "level0/20240101"))
+
+	assert.Equal(t, []string{
+		"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age",
+		"data/tank/data/level0/20240101/snapshot.part-aaaaab.age",
+		"data/tank/data/level0/20240101/snapshot.part-aaaaac.age",
+		"manifests/tank/data/level0/20240101/task_manifest.yaml",
+	}, backend.deleted)
+}
+
+func TestDeleteUploadedPartsOnForce(t *testing.T) {
+	task := &config.Task{Name: "t1", Pool: "tank", Dataset: "data"}
+
+	// the backup being overwritten has more parts than the new run will upload
+	backend := &fakeBackend{objects: map[string]bool{
+		"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age": true,
+		"data/tank/data/level0/20240101/snapshot.part-aaaaab.age": true,
+		"data/tank/data/level0/20240101/snapshot.part-aaaaac.age": true,
+		"manifests/tank/data/level0/20240101/task_manifest.yaml":  true,
+	}}
+	require.NoError(t, deleteUploadedParts(context.Background(), backend, true, task, "level0/20240101"))
+	assert.Equal(t, []string{
+		"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age",
+		"data/tank/data/level0/20240101/snapshot.part-aaaaab.age",
+		"data/tank/data/level0/20240101/snapshot.part-aaaaac.age",
+		"manifests/tank/data/level0/20240101/task_manifest.yaml",
+	}, backend.deleted)
+
+	backend = &fakeBackend{objects: map[string]bool{
+		"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age": true,
+	}}
+	require.NoError(t, deleteUploadedParts(context.Background(), backend, false, task, "level0/20240101"))
+	assert.Equal(t, []string{"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age"}, backend.deleted)
+}
@@ -22,7 +22,15 @@ import (
 	"filippo.io/age"
 )
 
-func Run(ctx context.Context, configPath string, backupLevel int16, taskName string) error {
+// now is the clock used for timestamps and scheduling; tests replace it.
+var now = time.Now
+
+const (
+	numWorkers        = 4 // TODO: make workers configurable
+	staleOutputDirAge = 7 * 24 * time.Hour
+)
+
+func Run(ctx context.Context, configPath string, backupLevel int16, taskName string, force, skipVerify, keepLocal bool) error {
 	if backupLevel < 0 {
 		return fmt.Errorf("backup level must be non-negative")
 	}
@@ -33,6 +41,8 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		return fmt.Errorf("backup cancelled before start: %w", ctx.Err())
 	}
 
+	startedAt := now()
+
 	// Load configuration
 	cfg, err := config.Load(configPath)
 	if err != nil {
@@ -59,12 +69,13 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	}
 
 	// Setup logging
-	logPath := filepath.Join(util.LogDir(cfg.BaseDir, task.Pool, task.Dataset), fmt.Sprintf("%s.log", time.Now().Format("2006-01-02")))
+	logPath := filepath.Join(util.LogDir(cfg.BaseDir, task.Pool, task.Dataset), fmt.Sprintf("%s.log", now().Format("2006-01-02")))
 	logger, logFile, err := util.SetupLogging(logPath)
 	if err != nil {
 		return fmt.Errorf("failed to setup logging: %w", err)
 	}
 	defer logFile.Close()
+	defer slog.SetDefault(slog.Default())
 	slog.SetDefault(logger)
 	slog.Info("Backup started", "level", backupLevel, "pool", task.Pool, "dataset", task.Dataset)
 
@@ -94,7 +105,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	}()
 
 	// List snapshots and determine target snapshot for backup
-	snapshots, err := zfs.ListSnapshots(task.Pool, task.Dataset, "zrb_level"+fmt.Sprint(backupLevel))
+	snapshots, err := zfs.ListSnapshots(task.Pool, task.Dataset, backupLevel)
 	if err != nil {
 		return fmt.Errorf("failed to list snapshots: %w", err)
 	}
@@ -108,7 +119,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	slog.Info("Target snapshot determined", "targetSnapshot", targetSnapshot, "count", len(snapshots))
 
 	// Determine task directory name
-	taskDirName := util.TaskDirName(backupLevel, time.Now())
+	taskDirName := util.TaskDirName(backupLevel, now())
 	if state.OutputDir != "" {
 		outputDirParent := filepath.Dir(state.OutputDir)
 		levelDir := filepath.Base(outputDirParent)
@@ -119,6 +130,9 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	// Ensure output directory
 	outputDir := filepath.Join(cfg.BaseDir, "task", task.Pool, task.Dataset, taskDirName)
 	if state.OutputDir == "" {
+		if _, err := os.Stat(filepath.Join(outputDir, "task_manifest.yaml")); err == nil && !force {
+			return fmt.Errorf("a completed backup already exists in %s, use --force to overwrite it", outputDir)
+		}
 		if _, err := os.Stat(outputDir); err == nil {
 			slog.Info("Cleaning up existing output directory", "path", outputDir)
 
@@ -131,6 +145,17 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		return fmt.Errorf("failed to create output directory: %w", err)
 	}
 
+	// With S3 enabled local parts are transient, so old output directories without a manifest belong to abandoned backups
+	if cfg.S3.Enabled {
+		datasetDir := filepath.Join(cfg.BaseDir, "task", task.Pool, task.Dataset)
+		freed, err := removeStaleOutputDirs(datasetDir, outputDir, staleOutputDirAge)
+		if err != nil {
+			slog.Warn("Failed to remove stale output directories", "error", err)
+		} else if freed > 0 {
+			slog.Info("Removed stale output directories", "freedBytes", freed)
+		}
+	}
+
 	// Determine parent snapshot
 	lastPath := filepath.Join(cfg.BaseDir, "run", task.Pool, task.Dataset, "last_backup_manifest.yaml")
 	var parentSnapshot string
@@ -142,62 +167,123 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 			return fmt.Errorf("failed to determine base for backup: %w", err)
 		}
 
-		if last.BackupLevels != nil && int16(len(last.BackupLevels)) >= backupLevel && last.BackupLevels[backupLevel-1] != nil {
-			// We have a previous backup at the required level
-			parentSnapshot = last.BackupLevels[backupLevel-1].Snapshot
-			slog.Info("Found parent snapshot from last backup manifest", "parentSnapshot", parentSnapshot)
-		} else {
-			return fmt.Errorf("failed to determine base for backup, no previous backups found")
+		parentRef, err := last.ParentRef(backupLevel)
+		if err != nil {
+			return fmt.Errorf("failed to determine base for backup: %w", err)
 		}
+		parentSnapshot = parentRef.Snapshot
+		slog.Info("Found parent snapshot from last backup manifest", "parentSnapshot", parentSnapshot)
 	}
 	// Resume from state if parent snapshot was already determined in a previous run
 	if state.ParentSnapshot != "" {
 		parentSnapshot = state.ParentSnapshot
 	}
 
+	// Initialize remote backend
+	var backend remote.Backend
+	var manifestBackend remote.Backend
+	if cfg.S3.Enabled {
+		maxRetryAttempts := cfg.S3RetryAttempts()
+		if int(backupLevel) >= len(cfg.S3.StorageClass.BackupData) {
+			return fmt.Errorf("backup level %d exceeds configured storage classes (only %d defined)", backupLevel, len(cfg.S3.StorageClass.BackupData))
+		}
+		storageClass := cfg.S3.StorageClass.BackupData[backupLevel]
+		s3Backend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region, cfg.S3.Prefix, cfg.S3.Endpoint, storageClass, maxRetryAttempts)
+		if err != nil {
+			return fmt.Errorf("failed to initialize S3 backend: %w", err)
+		}
+
+		backend = s3Backend
+		slog.Info("S3 backend initialized", "bucket", cfg.S3.Bucket, "region", cfg.S3.Region, "prefix", cfg.S3.Prefix)
+		if err := backend.VerifyCredentials(ctx); err != nil {
+			return fmt.Errorf("AWS credentials verification failed: %w", err)
+		}
+
+		mBackend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region, cfg.S3.Prefix, cfg.S3.Endpoint, cfg.S3.StorageClass.Manifest, maxRetryAttempts)
+		if err != nil {
+			return fmt.Errorf("failed to initialize S3 backend for manifests: %w", err)
+		}
+
+		manifestBackend = mBackend
+		slog.Info("S3 backend for manifests initialized")
+	}
+
 	if ctx.Err() != nil {
 		return fmt.Errorf("backup cancelled before ZFS send: %w", ctx.Err())
 	}
 
 	// Check zfs send and split already done
 	var blake3Hash string
+	var streamBytes int64
+	splitSize := state.SplitSize
 	if state.Blake3Hash == "" {
+		if err := zfs.CheckSnapshotExists(targetSnapshot); err != nil {
+			return fmt.Errorf("target snapshot: %w", err)
+		}
+		sendParent, err := sendSource(parentSnapshot)
+		if err != nil {
+			return fmt.Errorf("parent snapshot from level %d backup: %w", backupLevel-1, err)
+		}
+		if manifestBackend != nil && backupLevel > 0 {
+			if err := checkParentUploaded(ctx, manifestBackend, last, backupLevel); err != nil {
+				return fmt.Errorf("pre-flight check: %w", err)
+			}
+		}
+		if manifestBackend != nil && !force {
+			remoteManifestPath := filepath.Join("manifests", task.Pool, task.Dataset, taskDirName, "task_manifest.yaml")
+			if err := checkRemoteBackupAbsent(ctx, manifestBackend, remoteManifestPath); err != nil {
+				return fmt.Errorf("pre-flight check: %w", err)
+			}
+		}
+
+		estimated, err := zfs.EstimateSendSize(ctx, task.Send, targetSnapshot, sendParent)
+		if err != nil {
+			return fmt.Errorf("failed to estimate send size: %w", err)
+		}
+		splitSize = cfg.SplitSize(estimated)
+		slog.Info("Send size estimated", "estimatedBytes", estimated, "splitSize", splitSize)
+
+		available, err := util.AvailableSpace(outputDir)
+		if err != nil {
+			return fmt.Errorf("failed to check free space: %w", err)
+		}
+		required := requiredSpace(estimated, splitSize, cfg.FreeSpaceMarginMB)
+		if err := checkFreeSpace(outputDir, available, required); err != nil {
+			return fmt.Errorf("pre-flight check: %w", err)
+		}
+
 		// Need to run zfs send and split
 		slog.Info("Running zfs send and split", "targetSnapshot", targetSnapshot, "parentSnapshot", parentSnapshot)
-		blake3Hash, err = zfs.SendAndSplit(ctx, targetSnapshot, parentSnapshot, outputDir)
+		blake3Hash, streamBytes, err = zfs.SendAndSplit(ctx, task.Send, targetSnapshot, sendParent, outputDir, splitSize)
 		if err != nil {
 			return fmt.Errorf("failed to run zfs send and split: %w", err)
 		}
 		slog.Info("Snapshot BLAKE3", "hash", blake3Hash)
+
+		// A forced run may split into fewer parts than the backup it overwrites, whose extra parts would be orphaned
+		if backend != nil && force {
+			slog.Info("Deleting the remote backup being overwritten", "path", taskDirName)
+			if err := deleteUploadedParts(ctx, backend, true, task, taskDirName); err != nil {
+				return fmt.Errorf("failed to delete the overwritten backup: %w", err)
+			}
+		}
 	} else {
 		// Skip zfs send and split, resume from existing state
 		blake3Hash = state.Blake3Hash
+		streamBytes = state.StreamBytes
 		slog.Info("Using stored BLAKE3 hash", "hash", blake3Hash)
 	}
 
-	// Find snapshot part files (both raw and encrypted) and build unique index list
-	allParts, err := filepath.Glob(filepath.Join(outputDir, "snapshot.part-*"))
+	partIndices, err := discoverParts(outputDir)
 	if err != nil {
-		return fmt.Errorf("failed to find snapshot parts: %w", err)
-	}
-	partIndexSet := make(map[string]bool)
-	for _, part := range allParts {
-		baseName := filepath.Base(part)
-		baseName = strings.TrimSuffix(baseName, ".age")
-		index := strings.TrimPrefix(baseName, "snapshot.part-")
-		partIndexSet[index] = true
-	}
-	var partIndices []string
-	for idx := range partIndexSet {
-		partIndices = append(partIndices, idx)
+		return err
 	}
-	sort.Strings(partIndices)
-	if len(partIndices) == 0 {
-		return fmt.Errorf("no snapshot parts found in %s", outputDir)
+	if state.TaskName != "" {
+		slog.Info("Resuming backup", "stage", resumeStage(state, len(partIndices), manifestBackend != nil))
 	}
 
 	// Load encryption public key
-	recipient, err := age.ParseX25519Recipient(cfg.AgePublicKey)
+	recipient, err := crypto.ParseRecipient(cfg.AgePublicKey)
 	if err != nil {
 		return fmt.Errorf("failed to parse age public key: %w", err)
 	}
@@ -210,8 +296,11 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		state.ParentSnapshot = parentSnapshot
 		state.OutputDir = outputDir
 		state.Blake3Hash = blake3Hash
-		state.PartsCompleted = make(map[string]string)
-		state.LastUpdated = time.Now().Unix()
+		state.SplitSize = splitSize
+		state.StreamBytes = streamBytes
+		state.PartsCompleted = make(map[string]manifest.PartInfo)
+		state.LastUpdated = now().Unix()
+		state.StartedAt = state.LastUpdated
 
 		// Persist initial state to allow resuming if backup is interrupted during part processing
 		if err := manifest.WriteState(statePath, state); err != nil {
@@ -219,37 +308,8 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		}
 	}
 
-	// Initialize remote backend
-	var backend remote.Backend
-	var manifestBackend remote.Backend
-	if cfg.S3.Enabled {
-		maxRetryAttempts := cfg.S3RetryAttempts()
-		if int(backupLevel) >= len(cfg.S3.StorageClass.BackupData) {
-			return fmt.Errorf("backup level %d exceeds configured storage classes (only %d defined)", backupLevel, len(cfg.S3.StorageClass.BackupData))
-		}
-		storageClass := cfg.S3.StorageClass.BackupData[backupLevel]
-		s3Backend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region, cfg.S3.Prefix, cfg.S3.Endpoint, storageClass, maxRetryAttempts)
-		if err != nil {
-			return fmt.Errorf("failed to initialize S3 backend: %w", err)
-		}
-
-		backend = s3Backend
-		slog.Info("S3 backend initialized", "bucket", cfg.S3.Bucket, "region", cfg.S3.Region, "prefix", cfg.S3.Prefix)
-		if err := backend.VerifyCredentials(ctx); err != nil {
-			return fmt.Errorf("AWS credentials verification failed: %w", err)
-		}
-
-		mBackend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region, cfg.S3.Prefix, cfg.S3.Endpoint, cfg.S3.StorageClass.Manifest, maxRetryAttempts)
-		if err != nil {
-			return fmt.Errorf("failed to initialize S3 backend for manifests: %w", err)
-		}
-
-		manifestBackend = mBackend
-		slog.Info("S3 backend for manifests initialized")
-	}
-
 	// Process parts
-	partInfos, err := processPartsWithWorkerPool(ctx, partIndices, outputDir, state, statePath, recipient, backend, task, taskDirName, backupLevel)
+	partInfos, err := processPartsWithWorkerPool(ctx, partIndices, outputDir, state, statePath, recipient, backend, task, taskDirName, backupLevel, cfg.UploadWindow)
 	if err != nil {
 		return err
 	}
@@ -260,11 +320,8 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	})
 	slog.Info("All part files processed", "count", len(partInfos))
 
-	// Verify uploads via HeadObject (only level 0)
-	if backupLevel == 0 && backend != nil {
-		if err := verifyLevel0Parts(ctx, backend, partInfos, outputDir, task, taskDirName); err != nil {
-			return fmt.Errorf("level 0 verification failed: %w", err)
-		}
+	if err := verifyUploads(ctx, backend, backupLevel, skipVerify, partInfos, outputDir, task, taskDirName); err != nil {
+		return err
 	}
 
 	// Manifest management
@@ -284,7 +341,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		}
 
 		m := manifest.Backup{
-			Datetime:       time.Now().Unix(),
+			Datetime:       now().Unix(),
 			System:         systemInfo,
 			Pool:           task.Pool,
 			Dataset:        task.Dataset,
@@ -293,6 +350,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 			ParentSnapshot: parentSnapshot,
 			AgePublicKey:   cfg.AgePublicKey,
 			Blake3Hash:     blake3Hash,
+			SplitSize:      splitSize,
 			Parts:          partInfos,
 			TargetS3Path:   filepath.Join(task.Pool, task.Dataset, taskDirName),
 			ParentS3Path:   "",
@@ -308,7 +366,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		slog.Info("Manifest written", "path", manifestPath)
 
 		state.ManifestCreated = true
-		state.LastUpdated = time.Now().Unix()
+		state.LastUpdated = now().Unix()
 
 		if err := manifest.WriteState(statePath, state); err != nil {
 			slog.Warn("Failed to save backup state", "error", err)
@@ -329,7 +387,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		slog.Info("Manifest upload completed")
 
 		state.ManifestUploaded = true
-		state.LastUpdated = time.Now().Unix()
+		state.LastUpdated = now().Unix()
 
 		if err := manifest.WriteState(statePath, state); err != nil {
 			slog.Warn("Failed to save backup state", "error", err)
@@ -344,7 +402,7 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	currentLast.Pool = task.Pool
 	currentLast.Dataset = task.Dataset
 	ref := &manifest.Ref{
-		Datetime:   time.Now().Unix(),
+		Datetime:   now().Unix(),
 		Snapshot:   targetSnapshot,
 		Manifest:   manifestPath,
 		Blake3Hash: blake3Hash,
@@ -370,12 +428,36 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 	if err := zfs.Hold("zrb:last", targetSnapshot); err != nil {
 		slog.Warn("Failed to hold snapshot", "snapshot", targetSnapshot, "error", err)
 	}
+	if err := zfs.CheckBookmarkExists(zfs.BookmarkName(targetSnapshot)); err != nil {
+		if err := zfs.CreateBookmark(targetSnapshot); err != nil {
+			slog.Warn("Failed to bookmark snapshot", "snapshot", targetSnapshot, "error", err)
+		}
+	}
 
 	if err := manifest.WriteLast(lastPath, &currentLast); err != nil {
 		return fmt.Errorf("failed to write last backup manifest: %w", err)
 	}
 	slog.Info("Last backup manifest written", "path", lastPath)
 
+	historyPath := filepath.Join(runDir, "backup_history.yaml")
+	if err := manifest.AppendHistory(historyPath, &manifest.HistoryEntry{
+		Datetime:        ref.Datetime,
+		TaskName:        taskName,
+		Pool:            task.Pool,
+		Dataset:         task.Dataset,
+		BackupLevel:     backupLevel,
+		TargetSnapshot:  targetSnapshot,
+		ParentSnapshot:  parentSnapshot,
+		Blake3Hash:      blake3Hash,
+		Parts:           len(partInfos),
+		Bytes:           state.BytesCompleted(),
+		StreamBytes:     streamBytes,
+		DurationSeconds: int64(now().Sub(startedAt).Seconds()),
+		S3Path:          ref.S3Path,
+	}); err != nil {
+		slog.Warn("Failed to append backup history", "path", historyPath, "error", err)
+	}
+
 	// Release hold on old snapshot if different from current target snapshot
 	if oldSnapshot != "" && oldSnapshot != targetSnapshot {
 		if err := zfs.Release("zrb:last", oldSnapshot); err != nil {
@@ -397,26 +479,68 @@ func Run(ctx context.Context, configPath string, backupLevel int16, taskName str
 		slog.Info("Uploaded last backup manifest to remote", "remote", remoteLastPath)
 	}
 
-	if backend != nil {
-		slog.Info("Cleaning up local backup files", "path", outputDir)
-
-		if err := os.RemoveAll(outputDir); err != nil {
-			slog.Warn("Failed to clean up local files", "error", err)
-		}
-	}
+	cleanupOutputDir(backend, outputDir, keepLocal)
 
 	// Cleanup state file
 	if err := os.Remove(statePath); err != nil {
 		slog.Warn("Failed to remove backup state file", "error", err)
 	}
 
-	slog.Info("Backup completed successfully!")
+	slog.Info("Backup completed successfully!", "parts", len(partInfos), "streamBytes", streamBytes, "bytes", state.BytesCompleted(),
+		"duration", now().Sub(startedAt).Round(time.Second))
+
+	runPostBackupHook(ctx, cfg.PostBackupHook, map[string]string{
+		"task":     taskName,
+		"pool":     task.Pool,
+		"dataset":  task.Dataset,
+		"level":    fmt.Sprint(backupLevel),
+		"snapshot": targetSnapshot,
+		"blake3":   blake3Hash,
+	})
 	return nil
 }
 
+// discoverParts returns the sorted indices of the part files (raw or encrypted) in outputDir,
+// failing if the sequence split produced has a gap
+func discoverParts(outputDir string) ([]string, error) {
+	allParts, err := filepath.Glob(filepath.Join(outputDir, "snapshot.part-*"))
+	if err != nil {
+		return nil, fmt.Errorf("failed to find snapshot parts: %w", err)
+	}
+	partIndexSet := make(map[string]bool)
+	for _, part := range allParts {
+		baseName := filepath.Base(part)
+		baseName = strings.TrimSuffix(baseName, ".age")
+		index := strings.TrimPrefix(baseName, "snapshot.part-")
+		partIndexSet[index] = true
+	}
+	var partIndices []string
+	for idx := range partIndexSet {
+		partIndices = append(partIndices, idx)
+	}
+	sort.Strings(partIndices)
+	if len(partIndices) == 0 {
+		return nil, fmt.Errorf("no snapshot parts found in %s", outputDir)
+	}
+
+	for i, index := range partIndices {
+		if expected := zfs.PartSuffix(i); index != expected {
+			return nil, fmt.Errorf("snapshot part %s is missing in %s (found %s)", expected, outputDir, index)
+		}
+	}
+	return partIndices, nil
+}
+
 func loadOrCreateState(statePath, taskName string, backupLevel int16) (*manifest.State, error) {
-	if existingState, err := manifest.ReadState(statePath); err == nil && existingState != nil {
+	existingState, err := manifest.ReadState(statePath)
+	if err != nil && !os.IsNotExist(err) {
+		return nil, fmt.Errorf("failed to read backup state %s, run zrb abort to discard it: %w", statePath, err)
+	}
+	if existingState != nil {
 		if existingState.TaskName == taskName && existingState.BackupLevel == backupLevel {
+			if err := existingState.Validate(); err != nil {
+				return nil, fmt.Errorf("invalid backup state in %s, run zrb abort to discard it: %w", statePath, err)
+			}
 			slog.Info("Found existing backup state, resuming", "state", existingState)
 
 			return existingState, nil
@@ -439,11 +563,13 @@ func processPartsWithWorkerPool(
 	task *config.Task,
 	taskDirName string,
 	backupLevel int16,
+	uploadWindow *config.UploadWindow,
 ) ([]manifest.PartInfo, error) {
-	numWorkers := 4 // TODO: make workers configurable
 	var partInfos []manifest.PartInfo
 	var wg sync.WaitGroup
 	var stateMu sync.Mutex
+	var bytesThisRun int64
+	runStart := now()
 
 	partInfoChan := make(chan manifest.PartInfo, len(partIndices))
 	errChan := make(chan error, len(partIndices))
@@ -464,12 +590,12 @@ func processPartsWithWorkerPool(
 				}
 
 				stateMu.Lock()
-				completedHash := state.PartsCompleted[index]
+				completedPart, isCompleted := state.PartsCompleted[index]
 				stateMu.Unlock()
 
-				if completedHash != "" {
+				if isCompleted {
 					slog.Info("Skipping already completed part", "index", index)
-					partInfoChan <- manifest.PartInfo{Index: index, Blake3Hash: completedHash}
+					partInfoChan <- completedPart
 
 					continue
 				}
@@ -479,7 +605,19 @@ func processPartsWithWorkerPool(
 
 				var blake3Hash string
 
+				// A leftover raw file means encryption may have been interrupted
+				reuseEncrypted := false
 				if _, err := os.Stat(ageFile); err == nil {
+					if rawInfo, err := os.Stat(rawFile); err != nil {
+						reuseEncrypted = true
+					} else if err := crypto.VerifyEncrypted(ageFile, rawInfo.Size()); err != nil {
+						slog.Warn("Discarding incomplete encrypted file", "ageFile", ageFile, "error", err)
+					} else {
+						reuseEncrypted = true
+					}
+				}
+
+				if reuseEncrypted {
 					slog.Info("Found existing encrypted file, skipping encryption", "ageFile", ageFile)
 
 					var err error
@@ -505,7 +643,23 @@ func processPartsWithWorkerPool(
 					}
 				}
 
+				ageInfo, err := os.Stat(ageFile)
+				if err != nil {
+					slog.Error("Failed to stat encrypted file", "ageFile", ageFile, "error", err)
+					errChan <- err
+
+					continue
+				}
+				partInfo := manifest.PartInfo{Index: index, Blake3Hash: blake3Hash, Size: ageInfo.Size()}
+
 				if backend != nil {
+					if err := waitForUploadWindow(ctx, uploadWindow); err != nil {
+						slog.Warn("Worker stopping while waiting for upload window")
+						errChan <- err
+
+						return
+					}
+
 					if ctx.Err() != nil {
 						slog.Warn("Worker stopping before upload due to context cancellation")
 						errChan <- ctx.Err()
@@ -516,7 +670,7 @@ func processPartsWithWorkerPool(
 					slog.Info("Uploading part file to remote backend", "ageFile", ageFile)
 
 					remotePath := filepath.Join("data", task.Pool, task.Dataset, taskDirName, filepath.Base(ageFile))
-					if err := backend.Upload(ctx, ageFile, remotePath, blake3Hash, backupLevel); err != nil {
+					if err := uploadPart(ctx, backend, ageFile, remotePath, partInfo, backupLevel); err != nil {
 						slog.Error("Failed to upload part file", "ageFile", ageFile, "error", err)
 						errChan <- err
 
@@ -525,9 +679,12 @@ func processPartsWithWorkerPool(
 				}
 
 				stateMu.Lock()
-				state.PartsCompleted[index] = blake3Hash
-				state.LastUpdated = time.Now().Unix()
+				state.PartsCompleted[index] = partInfo
+				state.LastUpdated = now().Unix()
 				writeErr := manifest.WriteState(statePath, state)
+				bytesThisRun += partInfo.Size
+				completed := len(state.PartsCompleted)
+				eta, hasEta := estimateRemaining(now().Sub(runStart), bytesThisRun, remainingBytes(state))
 				stateMu.Unlock()
 
 				if writeErr != nil {
@@ -537,7 +694,11 @@ func processPartsWithWorkerPool(
 					return
 				}
 
-				partInfoChan <- manifest.PartInfo{Index: index, Blake3Hash: blake3Hash}
+				if hasEta {
+					slog.Info("Part completed", "index", index, "progress", fmt.Sprintf("%d/%d", completed, len(partIndices)), "eta", eta.Round(time.Second))
+				}
+
+				partInfoChan <- partInfo
 			}
 		}()
 	}
@@ -567,6 +728,189 @@ func processPartsWithWorkerPool(
 	return partInfos, nil
 }
 
+// removeStaleOutputDirs deletes level*/<date> directories under datasetDir, except activeDir and
+// completed backups, that have not been modified within olderThan, and returns the number of bytes freed
+func removeStaleOutputDirs(datasetDir, activeDir string, olderThan time.Duration) (int64, error) {
+	dirs, err := filepath.Glob(filepath.Join(datasetDir, "level*", "*"))
+	if err != nil {
+		return 0, err
+	}
+
+	var freed int64
+	cutoff := now().Add(-olderThan)
+	for _, dir := range dirs {
+		info, err := os.Stat(dir)
+		if err != nil || !info.IsDir() || filepath.Clean(dir) == filepath.Clean(activeDir) || info.ModTime().After(cutoff) {
+			continue
+		}
+		// A manifest means the backup completed and its files were kept on purpose (--keep-local)
+		if _, err := os.Stat(filepath.Join(dir, "task_manifest.yaml")); err == nil {
+			continue
+		}
+
+		var size int64
+		err = filepath.WalkDir(dir, func(_ string, d os.DirEntry, err error) error {
+			if err != nil {
+				return err
+			}
+			if fi, err := d.Info(); err == nil && !d.IsDir() {
+				size += fi.Size()
+			}
+			return nil
+		})
+		if err != nil {
+			return freed, fmt.Errorf("failed to scan %s: %w", dir, err)
+		}
+
+		slog.Info("Removing stale output directory", "path", dir, "bytes", size)
+		if err := os.RemoveAll(dir); err != nil {
+			return freed, fmt.Errorf("failed to remove %s: %w", dir, err)
+		}
+		freed += size
+	}
+
+	return freed, nil
+}
+
+// checkParentUploaded makes sure the backup an incremental is based on is still in the bucket,
+// otherwise the new backup could never be restored
+func checkParentUploaded(ctx context.Context, backend remote.Backend, last *manifest.Last, backupLevel int16) error {
+	parentRef, err := last.ParentRef(backupLevel)
+	if err != nil {
+		return err
+	}
+
+	remotePath := filepath.Join("manifests", parentRef.S3Path, "task_manifest.yaml")
+	if _, err := backend.Head(ctx, remotePath); err != nil {
+		return fmt.Errorf("level %d backup %s not found in remote storage: %w", backupLevel-1, parentRef.S3Path, err)
+	}
+	return nil
+}
+
+// checkRemoteBackupAbsent refuses to start a backup that would overwrite a completed one in the bucket
+func checkRemoteBackupAbsent(ctx context.Context, backend remote.Backend, remoteManifestPath string) error {
+	_, err := backend.Head(ctx, remoteManifestPath)
+	if err == nil {
+		return fmt.Errorf("backup already exists in remote storage at %s, use --force to overwrite it", remoteManifestPath)
+	}
+	if remote.IsNotFound(err) {
+		return nil
+	}
+	return fmt.Errorf("failed to check for existing backup: %w", err)
+}
+
+// sendSource returns what an incremental send is based on: the parent snapshot, or its
+// bookmark once the snapshot itself has been destroyed
+func sendSource(parentSnapshot string) (string, error) {
+	if parentSnapshot == "" {
+		return "", nil
+	}
+	snapshotErr := zfs.CheckSnapshotExists(parentSnapshot)
+	if snapshotErr == nil {
+		return parentSnapshot, nil
+	}
+
+	bookmark := zfs.BookmarkName(parentSnapshot)
+	if err := zfs.CheckBookmarkExists(bookmark); err != nil {
+		return "", snapshotErr
+	}
+	slog.Info("Parent snapshot no longer exists, sending from its bookmark", "bookmark", bookmark)
+	return bookmark, nil
+}
+
+// requiredSpace is the disk space a backup needs: the whole stream, plus a second copy of the
+// parts being encrypted at the same time (raw and .age exist until the raw file is removed)
+func requiredSpace(estimated, splitSize, marginMB int64) int64 {
+	return estimated + min(estimated, numWorkers*splitSize) + marginMB<<20
+}
+
+func checkFreeSpace(dir string, available, required int64) error {
+	if available < required {
+		return fmt.Errorf("insufficient free space in %s: %d bytes available, %d bytes required", dir, available, required)
+	}
+	return nil
+}
+
+// estimateRemaining projects the time left for the remaining bytes from the throughput of the processed ones
+func estimateRemaining(elapsed time.Duration, processed, remaining int64) (time.Duration, bool) {
+	if processed <= 0 || elapsed <= 0 || remaining < 0 {
+		return 0, false
+	}
+	return time.Duration(float64(elapsed) * float64(remaining) / float64(processed)), true
+}
+
+// remainingBytes is the part of the send stream not yet processed, -1 if the stream size is unknown
+func remainingBytes(state *manifest.State) int64 {
+	if state.StreamBytes <= 0 {
+		return -1
+	}
+	return max(state.StreamBytes-state.BytesCompleted(), 0)
+}
+
+// waitForUploadWindow blocks until the upload window is open. Completed parts are already persisted in the state file.
+func waitForUploadWindow(ctx context.Context, window *config.UploadWindow) error {
+	if window == nil {
+		return nil
+	}
+
+	current := now()
+	next := window.NextOpen(current)
+	if !next.After(current) {
+		return nil
+	}
+
+	slog.Info("Outside upload window, waiting", "resumeAt", next.Format(time.RFC3339))
+
+	timer := time.NewTimer(next.Sub(current))
+	defer timer.Stop()
+
+	select {
+	case <-ctx.Done():
+		return ctx.Err()
+	case <-timer.C:
+		return nil
+	}
+}
+
+// uploadPart uploads a part unless an identical object is already stored, e.g. by a run that was
+// interrupted after the upload but before its state was saved
+func uploadPart(ctx context.Context, backend remote.Backend, ageFile, remotePath string, partInfo manifest.PartInfo, backupLevel int16) error {
+	obj, err := backend.Head(ctx, remotePath)
+	if err != nil && !remote.IsNotFound(err) {
+		return fmt.Errorf("failed to check remote part: %w", err)
+	}
+	if err == nil && obj.Size == partInfo.Size && obj.Blake3 == partInfo.Blake3Hash {
+		slog.Info("Part already uploaded, skipping upload", "index", partInfo.Index, "remotePath", remotePath)
+		return nil
+	}
+
+	return backend.Upload(ctx, ageFile, remotePath, partInfo.Blake3Hash, backupLevel)
+}
+
+// verifyUploads checks the uploaded parts via HeadObject, only for level 0 and unless skipVerify
+func verifyUploads(ctx context.Context, backend remote.Backend, backupLevel int16, skipVerify bool, partInfos []manifest.PartInfo, outputDir string, task *config.Task, taskDirName string) error {
+	if backupLevel != 0 || backend == nil || skipVerify {
+		return nil
+	}
+	if err := verifyLevel0Parts(ctx, backend, partInfos, outputDir, task, taskDirName); err != nil {
+		return fmt.Errorf("level 0 verification failed: %w", err)
+	}
+	return nil
+}
+
+// cleanupOutputDir deletes the local backup files once they are uploaded, unless keepLocal.
+// Kept directories hold task_manifest.yaml, so removeStaleOutputDirs leaves them alone too
+func cleanupOutputDir(backend remote.Backend, outputDir string, keepLocal bool) {
+	if backend == nil || keepLocal {
+		return
+	}
+	slog.Info("Cleaning up local backup files", "path", outputDir)
+
+	if err := os.RemoveAll(outputDir); err != nil {
+		slog.Warn("Failed to clean up local files", "error", err)
+	}
+}
+
 func verifyLevel0Parts(ctx context.Context, backend remote.Backend, partInfos []manifest.PartInfo, outputDir string, task *config.Task, taskDirName string) error {
 	slog.Info("Verifying level 0 uploaded parts", "count", len(partInfos))
 
@@ -0,0 +1,316 @@
+package backup
+
+import (
+	"context"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+	"zrb/internal/config"
+	"zrb/internal/manifest"
+	"zrb/internal/remote"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func setClock(t *testing.T, fixed time.Time) {
+	t.Helper()
+	now = func() time.Time { return fixed }
+	t.Cleanup(func() { now = time.Now })
+}
+
+func TestWaitForUploadWindow(t *testing.T) {
+	window := &config.UploadWindow{Start: "01:00", End: "05:00"}
+
+	t.Run("no window", func(t *testing.T) {
+		assert.NoError(t, waitForUploadWindow(context.Background(), nil))
+	})
+
+	t.Run("inside window", func(t *testing.T) {
+		setClock(t, time.Date(2024, 1, 1, 2, 0, 0, 0, time.Local))
+		assert.NoError(t, waitForUploadWindow(context.Background(), window))
+	})
+
+	t.Run("outside window waits until cancelled", func(t *testing.T) {
+		setClock(t, time.Date(2024, 1, 1, 12, 0, 0, 0, time.Local))
+		ctx, cancel := context.WithTimeout(context.Background(), 50*time.Millisecond)
+		defer cancel()
+		assert.ErrorIs(t, waitForUploadWindow(ctx, window), context.DeadlineExceeded)
+	})
+}
+
+func TestEstimateRemaining(t *testing.T) {
+	tests := []struct {
+		name      string
+		elapsed   time.Duration
+		processed int64
+		remaining int64
+		want      time.Duration
+		wantOk    bool
+	}{
+		{name: "fresh run", elapsed: 0, processed: 0, remaining: 10 << 30, wantOk: false},
+		{name: "no part finished yet", elapsed: time.Minute, processed: 0, remaining: 10 << 30, wantOk: false},
+		{name: "steady throughput", elapsed: 4 * time.Minute, processed: 2 << 30, remaining: 6 << 30, want: 12 * time.Minute, wantOk: true},
+		{name: "last part done", elapsed: 5 * time.Minute, processed: 5 << 30, remaining: 0, want: 0, wantOk: true},
+		{name: "unknown stream size", elapsed: 5 * time.Minute, processed: 5 << 30, remaining: -1, wantOk: false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, ok := estimateRemaining(tt.elapsed, tt.processed, tt.remaining)
+			assert.Equal(t, tt.wantOk, ok)
+			assert.Equal(t, tt.want, got)
+		})
+	}
+}
+
+func TestCheckFreeSpace(t *testing.T) {
+	assert.NoError(t, checkFreeSpace("/tmp", 100, 100))
+	assert.NoError(t, checkFreeSpace("/tmp", 200, 100))
+	assert.ErrorContains(t, checkFreeSpace("/tmp", 99, 100), "insufficient free space in /tmp")
+}
+
+func TestRequiredSpace(t *testing.T) {
+	const splitSize = 3 << 30
+
+	tests := []struct {
+		name      string
+		estimated int64
+		marginMB  int64
+		expected  int64
+	}{
+		{"smaller than one part", 1 << 20, 0, 2 << 20},
+		{"smaller than one part with margin", 1 << 20, 100, 102 << 20},
+		{"more parts than workers", 100 << 30, 0, 100<<30 + numWorkers*splitSize},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			assert.Equal(t, tt.expected, requiredSpace(tt.estimated, splitSize, tt.marginMB))
+		})
+	}
+}
+
+func TestRemoveStaleOutputDirs(t *testing.T) {
+	datasetDir := t.TempDir()
+	old := time.Now().Add(-30 * 24 * time.Hour)
+
+	makeDir := func(rel string, size int, modTime time.Time) string {
+		dir := filepath.Join(datasetDir, rel)
+		require.NoError(t, os.MkdirAll(dir, 0o755))
+		require.NoError(t, os.WriteFile(filepath.Join(dir, "snapshot.part-aaaaaa"), make([]byte, size), 0o644))
+		require.NoError(t, os.Chtimes(dir, modTime, modTime))
+		return dir
+	}
+
+	stale := makeDir("level0/20240101", 100, old)
+	active := makeDir("level1/20240105", 200, old)
+	recent := makeDir("level1/20240301", 300, time.Now())
+	kept := makeDir("level0/20231201", 400, old)
+	require.NoError(t, os.WriteFile(filepath.Join(kept, "task_manifest.yaml"), []byte("pool: tank\n"), 0o644))
+	require.NoError(t, os.Chtimes(kept, old, old))
+
+	freed, err := removeStaleOutputDirs(datasetDir, active, 7*24*time.Hour)
+	require.NoError(t, err)
+	assert.Equal(t, int64(100), freed)
+
+	assert.NoDirExists(t, stale)
+	assert.DirExists(t, active)
+	assert.DirExists(t, recent)
+	assert.DirExists(t, kept)
+}
+
+func TestVerifyUploads(t *testing.T) {
+	task := &config.Task{Name: "t1", Pool: "tank", Dataset: "data"}
+	outputDir := t.TempDir()
+	require.NoError(t, os.WriteFile(filepath.Join(outputDir, "snapshot.part-aaaaaa.age"), make([]byte, 10), 0o644))
+	partInfos := []manifest.PartInfo{{Index: "aaaaaa", Blake3Hash: "h1", Size: 10}}
+	remotePath := "data/tank/data/level0/20240101/snapshot.part-aaaaaa.age"
+
+	tests := []struct {
+		name       string
+		level      int16
+		skipVerify bool
+		infos      map[string]*remote.ObjectInfo
+		wantErr    string
+	}{
+		{name: "uploaded part passes", infos: map[string]*remote.ObjectInfo{remotePath: {Size: 10, Blake3: "h1"}}},
+		{name: "missing part fails", wantErr: "level 0 verification failed"},
+		{name: "hash mismatch fails", infos: map[string]*remote.ObjectInfo{remotePath: {Size: 10, Blake3: "other"}}, wantErr: "BLAKE3 mismatch"},
+		{name: "skip verify ignores missing part", skipVerify: true},
+		{name: "level 1 is not verified", level: 1},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			backend := &fakeBackend{infos: tt.infos}
+			err := verifyUploads(context.Background(), backend, tt.level, tt.skipVerify, partInfos, outputDir, task, "level0/20240101")
+			if tt.wantErr != "" {
+				assert.ErrorContains(t, err, tt.wantErr)
+			} else {
+				assert.NoError(t, err)
+			}
+		})
+	}
+}
+
+func TestCleanupOutputDir(t *testing.T) {
+	tests := []struct {
+		name      string
+		backend   remote.Backend
+		keepLocal bool
+		wantKept  bool
+	}{
+		{name: "uploaded files are removed", backend: &fakeBackend{}},
+		{name: "keep local leaves files", backend: &fakeBackend{}, keepLocal: true, wantKept: true},
+		{name: "without S3 files stay", wantKept: true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			outputDir := filepath.Join(t.TempDir(), "level0", "20240101")
+			require.NoError(t, os.MkdirAll(outputDir, 0o755))
+			require.NoError(t, os.WriteFile(filepath.Join(outputDir, "snapshot.part-aaaaaa.age"), []byte("x"), 0o644))
+
+			cleanupOutputDir(tt.backend, outputDir, tt.keepLocal)
+
+			if tt.wantKept {
+				assert.FileExists(t, filepath.Join(outputDir, "snapshot.part-aaaaaa.age"))
+			} else {
+				assert.NoDirExists(t, outputDir)
+			}
+		})
+	}
+}
+
+func TestCheckParentUploaded(t *testing.T) {
+	last := &manifest.Last{
+		BackupLevels: []*manifest.Ref{
+			{Snapshot: "tank/data@zrb_level0_2024-01-01_00-00", S3Path: "tank/data/level0/20240101"},
+			{Snapshot: "tank/data@zrb_level1_2024-01-08_00-00", S3Path: "tank/data/level1/20240108"},
+		},
+	}
+	backend := &fakeBackend{objects: map[string]bool{
+		"manifests/tank/data/level0/20240101/task_manifest.yaml": true,
+	}}
+
+	assert.NoError(t, checkParentUploaded(context.Background(), backend, last, 1))
+	assert.ErrorContains(t, checkParentUploaded(context.Background(), backend, last, 2), "level 1 backup tank/data/level1/20240108 not found")
+	assert.ErrorContains(t, checkParentUploaded(context.Background(), backend, last, 3), "no level 2 backup found")
+}
+
+func TestExpandHook(t *testing.T) {
+	vars := map[string]string{"dataset": "data", "level": "1", "snapshot": "tank/my data@zrb_level1_x'y"}
+
+	assert.Equal(t, "notify 'data' 'data' level='1' {unknown}", expandHook("notify {dataset} {dataset} level={level} {unknown}", vars))
+	assert.Equal(t, `echo 'tank/my data@zrb_level1_x'\''y'`, expandHook("echo {snapshot}", vars))
+}
+
+func TestRunPostBackupHook(t *testing.T) {
+	out := filepath.Join(t.TempDir(), "hook.out")
+	vars := map[string]string{"task": "t1", "level": "0", "snapshot": "tank/my data@zrb_level0"}
+
+	runPostBackupHook(context.Background(), "printf '%s|%s|%s' {task} {level} {snapshot} > "+out, vars)
+	got, err := os.ReadFile(out)
+	require.NoError(t, err)
+	assert.Equal(t, "t1|0|tank/my data@zrb_level0", string(got))
+
+	// A failing hook must not panic or propagate
+	runPostBackupHook(context.Background(), "exit 3", vars)
+	runPostBackupHook(context.Background(), "", vars)
+}
+
+func TestDiscoverParts(t *testing.T) {
+	touch := func(dir string, names ...string) {
+		for _, name := range names {
+			require.NoError(t, os.WriteFile(filepath.Join(dir, name), nil, 0o644))
+		}
+	}
+
+	t.Run("raw and encrypted", func(t *testing.T) {
+		dir := t.TempDir()
+		touch(dir, "snapshot.part-aaaaaa.age", "snapshot.part-aaaaab.age", "snapshot.part-aaaaab", "snapshot.part-aaaaac")
+
+		indices, err := discoverParts(dir)
+		require.NoError(t, err)
+		assert.Equal(t, []string{"aaaaaa", "aaaaab", "aaaaac"}, indices)
+	})
+
+	t.Run("gap", func(t *testing.T) {
+		dir := t.TempDir()
+		touch(dir, "snapshot.part-aaaaaa", "snapshot.part-aaaaab", "snapshot.part-aaaaad")
+
+		_, err := discoverParts(dir)
+		assert.ErrorContains(t, err, "snapshot part aaaaac is missing")
+	})
+
+	t.Run("first part missing", func(t *testing.T) {
+		dir := t.TempDir()
+		touch(dir, "snapshot.part-aaaaab.age")
+
+		_, err := discoverParts(dir)
+		assert.ErrorContains(t, err, "snapshot part aaaaaa is missing")
+	})
+
+	t.Run("empty", func(t *testing.T) {
+		_, err := discoverParts(t.TempDir())
+		assert.ErrorContains(t, err, "no snapshot parts found")
+	})
+}
+
+func TestCheckRemoteBackupAbsent(t *testing.T) {
+	existing := "manifests/tank/data/level0/20240101/task_manifest.yaml"
+	backend := &fakeBackend{objects: map[string]bool{existing: true}}
+
+	assert.ErrorContains(t, checkRemoteBackupAbsent(context.Background(), backend, existing), "use --force to overwrite")
+	assert.NoError(t, checkRemoteBackupAbsent(context.Background(), backend, "manifests/tank/data/level0/20240102/task_manifest.yaml"))
+}
+
+func TestUploadPart(t *testing.T) {
+	remotePath := "data/tank/data/level0/20240101/snapshot.part-aaaaaa.age"
+	part := manifest.PartInfo{Index: "aaaaaa", Blake3Hash: "abc", Size: 100}
+
+	tests := []struct {
+		name     string
+		remote   *remote.ObjectInfo
+		uploaded bool
+	}{
+		{"not uploaded yet", nil, true},
+		{"identical object", &remote.ObjectInfo{Size: 100, Blake3: "abc"}, false},
+		{"different hash", &remote.ObjectInfo{Size: 100, Blake3: "def"}, true},
+		{"partial object", &remote.ObjectInfo{Size: 50, Blake3: "abc"}, true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			backend := &fakeBackend{infos: map[string]*remote.ObjectInfo{}}
+			if tt.remote != nil {
+				backend.infos[remotePath] = tt.remote
+			}
+
+			require.NoError(t, uploadPart(context.Background(), backend, "snapshot.part-aaaaaa.age", remotePath, part, 0))
+			if tt.uploaded {
+				assert.Equal(t, []string{remotePath}, backend.uploaded)
+			} else {
+				assert.Empty(t, backend.uploaded)
+			}
+		})
+	}
+}
+
+func TestLoadOrCreateState(t *testing.T) {
+	t.Run("missing starts fresh", func(t *testing.T) {
+		state, err := loadOrCreateState(filepath.Join(t.TempDir(), "backup_state.yaml"), "t1", 0)
+		require.NoError(t, err)
+		assert.Equal(t, &manifest.State{}, state)
+	})
+
+	t.Run("undecodable is reported", func(t *testing.T) {
+		statePath := filepath.Join(t.TempDir(), "backup_state.yaml")
+		require.NoError(t, os.WriteFile(statePath, []byte("parts_completed: [not, a, map]\n"), 0o644))
+
+		_, err := loadOrCreateState(statePath, "t1", 0)
+		assert.ErrorContains(t, err, "run zrb abort to discard it")
+	})
+}
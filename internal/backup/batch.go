@@ -0,0 +1,56 @@
+package backup
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"log/slog"
+	"zrb/internal/config"
+)
+
+// RunAll backs up every enabled task at the given level. A failing task does not stop
+// the remaining ones; all failures are returned together at the end.
+func RunAll(ctx context.Context, configPath string, backupLevel int16, force, skipVerify, keepLocal bool) error {
+	cfg, err := config.Load(configPath)
+	if err != nil {
+		return fmt.Errorf("failed to load config: %w", err)
+	}
+
+	var taskNames []string
+	for _, task := range cfg.Tasks {
+		if task.Enabled {
+			taskNames = append(taskNames, task.Name)
+		}
+	}
+	if len(taskNames) == 0 {
+		return fmt.Errorf("no enabled backup tasks in %s", configPath)
+	}
+
+	return runTasks(ctx, taskNames, func(taskName string) error {
+		return Run(ctx, configPath, backupLevel, taskName, force, skipVerify, keepLocal)
+	})
+}
+
+func runTasks(ctx context.Context, taskNames []string, run func(taskName string) error) error {
+	var errs []error
+	succeeded := 0
+	for _, taskName := range taskNames {
+		if ctx.Err() != nil {
+			errs = append(errs, fmt.Errorf("task %s: not started: %w", taskName, ctx.Err()))
+			continue
+		}
+
+		if err := run(taskName); err != nil {
+			slog.Error("Backup task failed", "task", taskName, "error", err)
+			errs = append(errs, fmt.Errorf("task %s: %w", taskName, err))
+			continue
+		}
+		succeeded++
+	}
+
+	slog.Info("Backup of all tasks finished", "succeeded", succeeded, "failed", len(errs))
+	if len(errs) > 0 {
+		return fmt.Errorf("%d of %d backup tasks failed: %w", len(errs), len(taskNames), errors.Join(errs...))
+	}
+	return nil
+}
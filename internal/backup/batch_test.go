@@ -0,0 +1,45 @@
+package backup
+
+import (
+	"context"
+	"errors"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestRunTasks(t *testing.T) {
+	t.Run("keeps going after a failure", func(t *testing.T) {
+		var ran []string
+		err := runTasks(context.Background(), []string{"t1", "t2", "t3"}, func(taskName string) error {
+			ran = append(ran, taskName)
+			if taskName == "t2" {
+				return errors.New("zfs send failed")
+			}
+			return nil
+		})
+
+		assert.Equal(t, []string{"t1", "t2", "t3"}, ran)
+		require.Error(t, err)
+		assert.ErrorContains(t, err, "1 of 3 backup tasks failed")
+		assert.ErrorContains(t, err, "task t2: zfs send failed")
+	})
+
+	t.Run("all succeed", func(t *testing.T) {
+		assert.NoError(t, runTasks(context.Background(), []string{"t1", "t2"}, func(string) error { return nil }))
+	})
+
+	t.Run("cancelled", func(t *testing.T) {
+		ctx, cancel := context.WithCancel(context.Background())
+		var ran []string
+		err := runTasks(ctx, []string{"t1", "t2"}, func(taskName string) error {
+			ran = append(ran, taskName)
+			cancel()
+			return nil
+		})
+
+		assert.Equal(t, []string{"t1"}, ran)
+		assert.ErrorIs(t, err, context.Canceled)
+	})
+}
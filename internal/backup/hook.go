@@ -0,0 +1,40 @@
+package backup
+
+import (
+	"context"
+	"log/slog"
+	"os/exec"
+	"strings"
+	"time"
+)
+
+const hookTimeout = 5 * time.Minute
+
+// expandHook substitutes {name} placeholders with shell-quoted values
+func expandHook(template string, vars map[string]string) string {
+	pairs := make([]string, 0, len(vars)*2)
+	for name, value := range vars {
+		pairs = append(pairs, "{"+name+"}", "'"+strings.ReplaceAll(value, "'", `'\''`)+"'")
+	}
+	return strings.NewReplacer(pairs...).Replace(template)
+}
+
+// runPostBackupHook runs the configured hook through sh; a failing hook is logged but never fails the backup
+func runPostBackupHook(ctx context.Context, template string, vars map[string]string) {
+	if template == "" {
+		return
+	}
+
+	hookCtx, cancel := context.WithTimeout(ctx, hookTimeout)
+	defer cancel()
+
+	command := expandHook(template, vars)
+	slog.Info("Running post-backup hook", "command", command)
+
+	output, err := exec.CommandContext(hookCtx, "sh", "-c", command).CombinedOutput()
+	if err != nil {
+		slog.Warn("Post-backup hook failed", "error", err, "output", strings.TrimSpace(string(output)))
+		return
+	}
+	slog.Info("Post-backup hook completed", "output", strings.TrimSpace(string(output)))
+}
@@ -0,0 +1,162 @@
+package backup
+
+import (
+	"context"
+	"fmt"
+	"path/filepath"
+	"zrb/internal/config"
+	"zrb/internal/manifest"
+	"zrb/internal/util"
+	"zrb/internal/zfs"
+)
+
+// Plan describes what a backup run would do without doing it
+type Plan struct {
+	Task           string
+	Level          int16
+	TargetSnapshot string
+	ParentSnapshot string
+	EstimatedBytes int64
+	SplitSize      int64
+	PartCount      int64
+	OutputDir      string
+	DataPrefix     string // empty when S3 is disabled
+	ManifestKey    string // empty when S3 is disabled
+	ResumeStage    string // set when an interrupted backup would be resumed instead
+}
+
+// DryRun resolves the snapshots and send size of a backup and prints the plan, changing nothing
+func DryRun(ctx context.Context, configPath string, backupLevel int16, taskName string) error {
+	cfg, err := config.Load(configPath)
+	if err != nil {
+		return fmt.Errorf("failed to load config: %w", err)
+	}
+
+	task, err := cfg.FindTask(taskName)
+	if err != nil {
+		return err
+	}
+	if !task.Enabled {
+		return fmt.Errorf("backup task is disabled: %s", taskName)
+	}
+
+	statePath := filepath.Join(util.RunDir(cfg.BaseDir, task.Pool, task.Dataset), "backup_state.yaml")
+	state, err := loadOrCreateState(statePath, taskName, backupLevel)
+	if err != nil {
+		return fmt.Errorf("failed to load backup state: %w", err)
+	}
+	if state.TaskName != "" {
+		totalParts := 0
+		if parts, err := discoverParts(state.OutputDir); err == nil {
+			totalParts = len(parts)
+		}
+		printPlan(cfg, buildResumePlan(cfg, task, state, totalParts))
+		return nil
+	}
+
+	snapshots, err := zfs.ListSnapshots(task.Pool, task.Dataset, backupLevel)
+	if err != nil {
+		return fmt.Errorf("failed to list snapshots: %w", err)
+	}
+	if len(snapshots) == 0 {
+		return fmt.Errorf("no snapshots found for pool=%s dataset=%s", task.Pool, task.Dataset)
+	}
+	targetSnapshot := snapshots[0]
+
+	var parentSnapshot string
+	if backupLevel > 0 {
+		lastPath := filepath.Join(util.RunDir(cfg.BaseDir, task.Pool, task.Dataset), "last_backup_manifest.yaml")
+		last, err := manifest.ReadLast(lastPath)
+		if err != nil {
+			return fmt.Errorf("failed to determine base for backup: %w", err)
+		}
+		parentRef, err := last.ParentRef(backupLevel)
+		if err != nil {
+			return fmt.Errorf("failed to determine base for backup: %w", err)
+		}
+		parentSnapshot = parentRef.Snapshot
+	}
+
+	sendParent, err := sendSource(parentSnapshot)
+	if err != nil {
+		return fmt.Errorf("parent snapshot from level %d backup: %w", backupLevel-1, err)
+	}
+
+	estimated, err := zfs.EstimateSendSize(ctx, task.Send, targetSnapshot, sendParent)
+	if err != nil {
+		return fmt.Errorf("failed to estimate send size: %w", err)
+	}
+
+	printPlan(cfg, buildPlan(cfg, task, backupLevel, targetSnapshot, parentSnapshot, estimated, util.TaskDirName(backupLevel, now())))
+	return nil
+}
+
+func printPlan(cfg *config.Config, plan Plan) {
+	fmt.Printf("\n=== DRY RUN MODE ===\n")
+	if plan.ResumeStage != "" {
+		fmt.Printf("Would resume interrupted backup:\n")
+	} else {
+		fmt.Printf("Would run backup:\n")
+	}
+	fmt.Printf("  Task:            %s\n", plan.Task)
+	fmt.Printf("  Backup Level:    %d\n", plan.Level)
+	fmt.Printf("  Snapshot:        %s\n", plan.TargetSnapshot)
+	if plan.ParentSnapshot != "" {
+		fmt.Printf("  Parent Snapshot: %s\n", plan.ParentSnapshot)
+	}
+	if plan.ResumeStage != "" {
+		fmt.Printf("  Resumes At:      %s\n", plan.ResumeStage)
+	} else {
+		fmt.Printf("  Estimated Size:  %d bytes\n", plan.EstimatedBytes)
+	}
+	fmt.Printf("  Parts:           %d x %d bytes\n", plan.PartCount, plan.SplitSize)
+	fmt.Printf("  Output Dir:      %s\n", plan.OutputDir)
+	if plan.DataPrefix != "" {
+		fmt.Printf("  S3 Data:         s3://%s/%s\n", cfg.S3.Bucket, plan.DataPrefix)
+		fmt.Printf("  S3 Manifest:     s3://%s/%s\n", cfg.S3.Bucket, plan.ManifestKey)
+	}
+	fmt.Printf("\nNo changes made.\n")
+}
+
+func buildPlan(cfg *config.Config, task *config.Task, backupLevel int16, targetSnapshot, parentSnapshot string, estimated int64, taskDirName string) Plan {
+	splitSize := cfg.SplitSize(estimated)
+	plan := Plan{
+		Task:           task.Name,
+		Level:          backupLevel,
+		TargetSnapshot: targetSnapshot,
+		ParentSnapshot: parentSnapshot,
+		EstimatedBytes: estimated,
+		SplitSize:      splitSize,
+		PartCount:      max((estimated+splitSize-1)/splitSize, 1),
+		OutputDir:      filepath.Join(cfg.BaseDir, "task", task.Pool, task.Dataset, taskDirName),
+	}
+
+	setRemoteKeys(&plan, cfg, task, taskDirName)
+	return plan
+}
+
+// buildResumePlan describes what Run does with the interrupted backup in state, which it resumes
+// regardless of newer snapshots; totalParts is 0 when the part files are not found
+func buildResumePlan(cfg *config.Config, task *config.Task, state *manifest.State, totalParts int) Plan {
+	plan := Plan{
+		Task:           task.Name,
+		Level:          state.BackupLevel,
+		TargetSnapshot: state.TargetSnapshot,
+		ParentSnapshot: state.ParentSnapshot,
+		SplitSize:      state.SplitSize,
+		PartCount:      int64(totalParts),
+		OutputDir:      state.OutputDir,
+		ResumeStage:    resumeStage(state, totalParts, cfg.S3.Enabled),
+	}
+
+	taskDirName := filepath.Join(filepath.Base(filepath.Dir(state.OutputDir)), filepath.Base(state.OutputDir))
+	setRemoteKeys(&plan, cfg, task, taskDirName)
+	return plan
+}
+
+func setRemoteKeys(plan *Plan, cfg *config.Config, task *config.Task, taskDirName string) {
+	if cfg.S3.Enabled {
+		plan.DataPrefix = filepath.ToSlash(filepath.Join(cfg.S3.Prefix, "data", task.Pool, task.Dataset, taskDirName)) + "/"
+		plan.ManifestKey = filepath.ToSlash(filepath.Join(cfg.S3.Prefix, "manifests", task.Pool, task.Dataset, taskDirName, "task_manifest.yaml"))
+	}
+}
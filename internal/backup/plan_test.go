@@ -0,0 +1,72 @@
+package backup
+
+import (
+	"testing"
+	"zrb/internal/config"
+	"zrb/internal/manifest"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestBuildPlan(t *testing.T) {
+	task := &config.Task{Name: "t1", Pool: "tank", Dataset: "data"}
+
+	t.Run("local only", func(t *testing.T) {
+		cfg := &config.Config{BaseDir: "/var/zrb"}
+		plan := buildPlan(cfg, task, 0, "tank/data@zrb_level0_2024-01-01_00-00", "", 10<<30, "level0/20240101")
+
+		assert.Equal(t, config.DefaultSplitSize, plan.SplitSize)
+		assert.Equal(t, int64(4), plan.PartCount)
+		assert.Equal(t, "/var/zrb/task/tank/data/level0/20240101", plan.OutputDir)
+		assert.Empty(t, plan.DataPrefix)
+		assert.Empty(t, plan.ManifestKey)
+	})
+
+	t.Run("adaptive split to S3", func(t *testing.T) {
+		cfg := &config.Config{
+			BaseDir: "/var/zrb",
+			S3:      config.S3Config{Enabled: true, Bucket: "bucket", Prefix: "zrb"},
+			Split:   config.SplitConfig{TargetCount: 5, MinSizeMB: 64, MaxSizeMB: 4096},
+		}
+		plan := buildPlan(cfg, task, 1, "tank/data@zrb_level1_2024-01-08_00-00", "tank/data@zrb_level0_2024-01-01_00-00", 1000<<20, "level1/20240108")
+
+		assert.Equal(t, int64(200<<20), plan.SplitSize)
+		assert.Equal(t, int64(5), plan.PartCount)
+		assert.Equal(t, "zrb/data/tank/data/level1/20240108/", plan.DataPrefix)
+		assert.Equal(t, "zrb/manifests/tank/data/level1/20240108/task_manifest.yaml", plan.ManifestKey)
+	})
+
+	t.Run("empty stream", func(t *testing.T) {
+		plan := buildPlan(&config.Config{BaseDir: "/var/zrb"}, task, 1, "a", "b", 0, "level1/20240108")
+		assert.Equal(t, int64(1), plan.PartCount)
+	})
+}
+
+func TestBuildResumePlan(t *testing.T) {
+	task := &config.Task{Name: "t1", Pool: "tank", Dataset: "data"}
+	cfg := &config.Config{
+		BaseDir: "/var/zrb",
+		S3:      config.S3Config{Enabled: true, Bucket: "bucket", Prefix: "zrb"},
+	}
+	state := &manifest.State{
+		TaskName:       "t1",
+		BackupLevel:    1,
+		TargetSnapshot: "tank/data@zrb_level1_2024-01-08_00-00",
+		ParentSnapshot: "tank/data@zrb_level0_2024-01-01_00-00",
+		OutputDir:      "/var/zrb/task/tank/data/level1/20240108",
+		Blake3Hash:     "h",
+		SplitSize:      64 << 20,
+		PartsCompleted: map[string]manifest.PartInfo{"aaaaaa": {Index: "aaaaaa", Blake3Hash: "h1"}},
+	}
+
+	plan := buildResumePlan(cfg, task, state, 3)
+
+	assert.Equal(t, state.TargetSnapshot, plan.TargetSnapshot)
+	assert.Equal(t, state.ParentSnapshot, plan.ParentSnapshot)
+	assert.Equal(t, int64(64<<20), plan.SplitSize)
+	assert.Equal(t, int64(3), plan.PartCount)
+	assert.Equal(t, state.OutputDir, plan.OutputDir)
+	assert.Equal(t, "part processing (1/3 completed)", plan.ResumeStage)
+	assert.Equal(t, "zrb/data/tank/data/level1/20240108/", plan.DataPrefix)
+	assert.Equal(t, "zrb/manifests/tank/data/level1/20240108/task_manifest.yaml", plan.ManifestKey)
+}
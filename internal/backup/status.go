@@ -0,0 +1,89 @@
+package backup
+
+import (
+	"context"
+	"fmt"
+	"os"
+	"path/filepath"
+	"time"
+	"zrb/internal/config"
+	"zrb/internal/manifest"
+	"zrb/internal/util"
+)
+
+// Status prints where an interrupted backup of a task stands and where the next run resumes, changing nothing
+func Status(ctx context.Context, configPath, taskName string) error {
+	cfg, err := config.Load(configPath)
+	if err != nil {
+		return fmt.Errorf("failed to load config: %w", err)
+	}
+
+	task, err := cfg.FindTask(taskName)
+	if err != nil {
+		return err
+	}
+
+	statePath := filepath.Join(util.RunDir(cfg.BaseDir, task.Pool, task.Dataset), "backup_state.yaml")
+	state, err := manifest.ReadState(statePath)
+	if err != nil {
+		if os.IsNotExist(err) {
+			fmt.Printf("No backup in progress for task %s\n", taskName)
+			return nil
+		}
+		return fmt.Errorf("failed to read backup state: %w", err)
+	}
+	if err := state.Validate(); err != nil {
+		return fmt.Errorf("invalid backup state in %s, run zrb abort to discard it: %w", statePath, err)
+	}
+
+	totalParts := 0
+	if state.Blake3Hash != "" {
+		if parts, err := discoverParts(state.OutputDir); err == nil {
+			totalParts = len(parts)
+		}
+	}
+
+	fmt.Printf("Backup in progress:\n")
+	fmt.Printf("  Task:            %s\n", state.TaskName)
+	fmt.Printf("  Backup Level:    %d\n", state.BackupLevel)
+	fmt.Printf("  Snapshot:        %s\n", state.TargetSnapshot)
+	if state.ParentSnapshot != "" {
+		fmt.Printf("  Parent Snapshot: %s\n", state.ParentSnapshot)
+	}
+	fmt.Printf("  Output:          %s\n", state.OutputDir)
+	fmt.Printf("  Last Updated:    %s\n", time.Unix(state.LastUpdated, 0).Format("2006-01-02 15:04:05"))
+	fmt.Printf("  Resumes At:      %s\n", resumeStage(state, totalParts, cfg.S3.Enabled))
+	if len(state.PartsCompleted) < totalParts {
+		if eta, ok := stateETA(state); ok {
+			fmt.Printf("  ETA:             ~%s\n", eta.Round(time.Minute))
+		}
+	}
+	return nil
+}
+
+// stateETA estimates the time left for the remaining parts from the throughput recorded in state
+func stateETA(state *manifest.State) (time.Duration, bool) {
+	if state.StartedAt == 0 {
+		return 0, false
+	}
+	elapsed := time.Duration(state.LastUpdated-state.StartedAt) * time.Second
+	return estimateRemaining(elapsed, state.BytesCompleted(), remainingBytes(state))
+}
+
+// resumeStage describes the stage a run resumes at for state; totalParts is 0 when the parts are unknown
+func resumeStage(state *manifest.State, totalParts int, s3Enabled bool) string {
+	switch {
+	case state.Blake3Hash == "":
+		return "zfs send"
+	case totalParts == 0:
+		return "part processing (part files not found)"
+	case len(state.PartsCompleted) < totalParts:
+		return fmt.Sprintf("part processing (%d/%d completed)", len(state.PartsCompleted), totalParts)
+	case !state.ManifestCreated:
+		return "manifest creation"
+	case s3Enabled && !state.ManifestUploaded:
+		return "manifest upload"
+	default:
+		return "finalization"
+	}
+}
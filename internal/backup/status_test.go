@@ -0,0 +1,75 @@
+package backup
+
+import (
+	"testing"
+	"time"
+	"zrb/internal/manifest"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestResumeStage(t *testing.T) {
+	parts := map[string]manifest.PartInfo{
+		"aaaaaa": {Index: "aaaaaa", Blake3Hash: "h1"},
+		"aaaaab": {Index: "aaaaab", Blake3Hash: "h2"},
+	}
+
+	tests := []struct {
+		name       string
+		state      manifest.State
+		totalParts int
+		s3Enabled  bool
+		expected   string
+	}{
+		{"before send", manifest.State{}, 0, true, "zfs send"},
+		{"parts missing", manifest.State{Blake3Hash: "h"}, 0, true, "part processing (part files not found)"},
+		{"mid parts", manifest.State{Blake3Hash: "h", PartsCompleted: parts}, 5, true, "part processing (2/5 completed)"},
+		{"parts done", manifest.State{Blake3Hash: "h", PartsCompleted: parts}, 2, true, "manifest creation"},
+		{"manifest created", manifest.State{Blake3Hash: "h", PartsCompleted: parts, ManifestCreated: true}, 2, true, "manifest upload"},
+		{"manifest created local only", manifest.State{Blake3Hash: "h", PartsCompleted: parts, ManifestCreated: true}, 2, false, "finalization"},
+		{"manifest uploaded", manifest.State{Blake3Hash: "h", PartsCompleted: parts, ManifestCreated: true, ManifestUploaded: true}, 2, true, "finalization"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			assert.Equal(t, tt.expected, resumeStage(&tt.state, tt.totalParts, tt.s3Enabled))
+		})
+	}
+}
+
+func TestStateETA(t *testing.T) {
+	started := time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC).Unix()
+	parts := func(sizes ...int64) map[string]manifest.PartInfo {
+		m := make(map[string]manifest.PartInfo)
+		for i, size := range sizes {
+			index := string(rune('a' + i))
+			m[index] = manifest.PartInfo{Index: index, Size: size}
+		}
+		return m
+	}
+
+	tests := []struct {
+		name   string
+		state  manifest.State
+		want   time.Duration
+		wantOk bool
+	}{
+		{name: "fresh task", state: manifest.State{StreamBytes: 8 << 30, StartedAt: started, LastUpdated: started, PartsCompleted: parts()}},
+		{name: "unknown stream size", state: manifest.State{StartedAt: started, LastUpdated: started + 600, PartsCompleted: parts(1 << 30)}},
+		{name: "state before start time was recorded", state: manifest.State{StreamBytes: 8 << 30, LastUpdated: started + 600, PartsCompleted: parts(1 << 30)}},
+		{
+			name:   "two parts in ten minutes",
+			state:  manifest.State{StreamBytes: 8 << 30, StartedAt: started, LastUpdated: started + 600, PartsCompleted: parts(1<<30, 1<<30)},
+			want:   30 * time.Minute,
+			wantOk: true,
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, ok := stateETA(&tt.state)
+			assert.Equal(t, tt.wantOk, ok)
+			assert.Equal(t, tt.want, got)
+		})
+	}
+}
@@ -2,13 +2,84 @@ package check
 
 import (
 	"context"
+	"errors"
 	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strconv"
+	"strings"
+	"time"
 	"zrb/internal/config"
+	"zrb/internal/manifest"
 	"zrb/internal/remote"
+	"zrb/internal/util"
 	"zrb/internal/zfs"
 )
 
-func Run(ctx context.Context, configPath string) error {
+// MaxAge limits how old the backups of a task may be
+type MaxAge struct {
+	Any    time.Duration           // for the newest backup of any level
+	Levels map[int16]time.Duration // for the latest backup of each listed level
+}
+
+// ParseMaxAge parses a limit for the newest backup of any level, e.g. 36h, and/or limits per level,
+// e.g. 0=35d,1=8d,2=2d. Durations accept a d suffix for days.
+func ParseMaxAge(s string) (MaxAge, error) {
+	var maxAge MaxAge
+	if s == "" {
+		return maxAge, nil
+	}
+
+	for _, item := range strings.Split(s, ",") {
+		levelStr, ageStr, perLevel := strings.Cut(strings.TrimSpace(item), "=")
+		if !perLevel {
+			ageStr = levelStr
+		}
+		age, err := parseAge(ageStr)
+		if err != nil {
+			return MaxAge{}, fmt.Errorf("invalid max age %q: %w", item, err)
+		}
+		if !perLevel {
+			maxAge.Any = age
+			continue
+		}
+
+		level, err := strconv.ParseInt(levelStr, 10, 16)
+		if err != nil || level < 0 {
+			return MaxAge{}, fmt.Errorf("invalid backup level in max age %q", item)
+		}
+		if maxAge.Levels == nil {
+			maxAge.Levels = make(map[int16]time.Duration)
+		}
+		maxAge.Levels[int16(level)] = age
+	}
+	return maxAge, nil
+}
+
+func parseAge(s string) (time.Duration, error) {
+	var age time.Duration
+	if days, found := strings.CutSuffix(s, "d"); found {
+		n, err := strconv.Atoi(days)
+		if err != nil {
+			return 0, err
+		}
+		age = time.Duration(n) * 24 * time.Hour
+	} else {
+		var err error
+		if age, err = time.ParseDuration(s); err != nil {
+			return 0, err
+		}
+	}
+	if age <= 0 {
+		return 0, fmt.Errorf("must be positive")
+	}
+	return age, nil
+}
+
+// Run validates the configuration, datasets and S3 access. With maxAge set it also
+// fails when an enabled task has no successful backup within the allowed age.
+func Run(ctx context.Context, configPath string, maxAge MaxAge) error {
 	cfg, err := config.Load(configPath)
 	if err != nil {
 		return fmt.Errorf("config: %w", err)
@@ -24,6 +95,14 @@ func Run(ctx context.Context, configPath string) error {
 			return fmt.Errorf("task %s: %w", task.Name, err)
 		}
 		fmt.Printf("task %s dataset %s/%s: OK\n", task.Name, task.Pool, task.Dataset)
+
+		snapshots, err := zfs.ListSnapshotsDetailed(task.Pool, task.Dataset)
+		if err != nil {
+			return fmt.Errorf("task %s: failed to list snapshots: %w", task.Name, err)
+		}
+		for _, s := range latestSnapshots(snapshots) {
+			fmt.Printf("task %s latest snapshot %s: created %s ago\n", task.Name, s.Name, time.Since(s.Created).Round(time.Minute))
+		}
 	}
 
 	if cfg.S3.Enabled {
@@ -39,6 +118,101 @@ func Run(ctx context.Context, configPath string) error {
 		fmt.Printf("S3 bucket %s: OK\n", cfg.S3.Bucket)
 	}
 
+	if maxAge.Any > 0 || len(maxAge.Levels) > 0 {
+		stale := 0
+		for _, task := range cfg.Tasks {
+			if !task.Enabled {
+				continue
+			}
+			lastPath := filepath.Join(util.RunDir(cfg.BaseDir, task.Pool, task.Dataset), "last_backup_manifest.yaml")
+			last, err := manifest.ReadLast(lastPath)
+			if err != nil && !os.IsNotExist(err) {
+				return fmt.Errorf("task %s: failed to read last backup manifest: %w", task.Name, err)
+			}
+			if err := checkBackupAge(last, maxAge, time.Now()); err != nil {
+				fmt.Printf("task %s backup age: %v\n", task.Name, err)
+				stale++
+				continue
+			}
+			fmt.Printf("task %s backup age: OK\n", task.Name)
+		}
+		if stale > 0 {
+			return fmt.Errorf("%d task(s) without a recent enough backup", stale)
+		}
+	}
+
 	fmt.Println("all checks passed")
 	return nil
 }
+
+// latestSnapshots returns the newest zrb snapshot of each level, ordered by level; external snapshots are ignored
+func latestSnapshots(snapshots []zfs.SnapshotInfo) []zfs.SnapshotInfo {
+	latest := map[int16]zfs.SnapshotInfo{}
+	for _, s := range snapshots {
+		info, ok := zfs.ParseSnapshotName(s.Name)
+		if !ok {
+			continue
+		}
+		if cur, found := latest[info.Level]; !found || s.Created.After(cur.Created) {
+			latest[info.Level] = s
+		}
+	}
+
+	levels := make([]int16, 0, len(latest))
+	for level := range latest {
+		levels = append(levels, level)
+	}
+	sort.Slice(levels, func(i, j int) bool { return levels[i] < levels[j] })
+
+	result := make([]zfs.SnapshotInfo, 0, len(levels))
+	for _, level := range levels {
+		result = append(result, latest[level])
+	}
+	return result
+}
+
+// checkBackupAge fails when the most recent backup of any level is older than maxAge.Any,
+// or the latest backup of a level is missing or older than its limit in maxAge.Levels
+func checkBackupAge(last *manifest.Last, maxAge MaxAge, now time.Time) error {
+	var levels []*manifest.Ref
+	if last != nil {
+		levels = last.BackupLevels
+	}
+
+	var problems []string
+	if maxAge.Any > 0 {
+		var latest int64
+		for _, ref := range levels {
+			if ref != nil && ref.Datetime > latest {
+				latest = ref.Datetime
+			}
+		}
+		if latest == 0 {
+			problems = append(problems, "no backup found")
+		} else if age := now.Sub(time.Unix(latest, 0)); age > maxAge.Any {
+			problems = append(problems, fmt.Sprintf("last backup was %s ago, exceeds %s", age.Round(time.Minute), maxAge.Any))
+		}
+	}
+
+	limited := make([]int16, 0, len(maxAge.Levels))
+	for level := range maxAge.Levels {
+		limited = append(limited, level)
+	}
+	sort.Slice(limited, func(i, j int) bool { return limited[i] < limited[j] })
+
+	for _, level := range limited {
+		limit := maxAge.Levels[level]
+		if int(level) >= len(levels) || levels[level] == nil {
+			problems = append(problems, fmt.Sprintf("no level %d backup found", level))
+			continue
+		}
+		if age := now.Sub(time.Unix(levels[level].Datetime, 0)); age > limit {
+			problems = append(problems, fmt.Sprintf("last level %d backup was %s ago, exceeds %s", level, age.Round(time.Minute), limit))
+		}
+	}
+
+	if len(problems) > 0 {
+		return errors.New(strings.Join(problems, "; "))
+	}
+	return nil
+}
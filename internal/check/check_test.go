@@ -0,0 +1,131 @@
+package check
+
+import (
+	"testing"
+	"time"
+	"zrb/internal/manifest"
+	"zrb/internal/zfs"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestCheckBackupAge(t *testing.T) {
+	now := time.Date(2024, 1, 10, 12, 0, 0, 0, time.UTC)
+	maxAge := 48 * time.Hour
+	at := func(d time.Duration) *manifest.Ref { return &manifest.Ref{Datetime: now.Add(-d).Unix()} }
+
+	tests := []struct {
+		name    string
+		last    *manifest.Last
+		wantErr string
+	}{
+		{name: "no manifest", last: nil, wantErr: "no backup found"},
+		{name: "no levels", last: &manifest.Last{}, wantErr: "no backup found"},
+		{name: "fresh full", last: &manifest.Last{BackupLevels: []*manifest.Ref{at(time.Hour)}}},
+		{name: "fresh incremental", last: &manifest.Last{BackupLevels: []*manifest.Ref{at(30 * 24 * time.Hour), at(time.Hour)}}},
+		{name: "pruned level", last: &manifest.Last{BackupLevels: []*manifest.Ref{nil, at(time.Hour)}}},
+		{name: "stale", last: &manifest.Last{BackupLevels: []*manifest.Ref{at(72 * time.Hour)}}, wantErr: "last backup was 72h0m0s ago, exceeds 48h0m0s"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			err := checkBackupAge(tt.last, MaxAge{Any: maxAge}, now)
+			if tt.wantErr != "" {
+				assert.ErrorContains(t, err, tt.wantErr)
+				return
+			}
+			assert.NoError(t, err)
+		})
+	}
+}
+
+func TestCheckBackupAgePerLevel(t *testing.T) {
+	now := time.Date(2024, 1, 10, 12, 0, 0, 0, time.UTC)
+	maxAge := MaxAge{Levels: map[int16]time.Duration{0: 35 * 24 * time.Hour, 1: 8 * 24 * time.Hour, 2: 2 * 24 * time.Hour}}
+	at := func(d time.Duration) *manifest.Ref { return &manifest.Ref{Datetime: now.Add(-d).Unix()} }
+	monthly, weekly, daily := at(30*24*time.Hour), at(6*24*time.Hour), at(12*time.Hour)
+
+	tests := []struct {
+		name    string
+		last    *manifest.Last
+		wantErr string
+	}{
+		{name: "every level fresh", last: &manifest.Last{BackupLevels: []*manifest.Ref{monthly, weekly, daily}}},
+		{name: "no manifest", last: nil, wantErr: "no level 0 backup found; no level 1 backup found; no level 2 backup found"},
+		{name: "missing level", last: &manifest.Last{BackupLevels: []*manifest.Ref{monthly, nil, daily}}, wantErr: "no level 1 backup found"},
+		{
+			name:    "stale daily",
+			last:    &manifest.Last{BackupLevels: []*manifest.Ref{monthly, weekly, at(72 * time.Hour)}},
+			wantErr: "last level 2 backup was 72h0m0s ago, exceeds 48h0m0s",
+		},
+		{
+			name:    "stale full",
+			last:    &manifest.Last{BackupLevels: []*manifest.Ref{at(40 * 24 * time.Hour), weekly, daily}},
+			wantErr: "last level 0 backup was 960h0m0s ago, exceeds 840h0m0s",
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			err := checkBackupAge(tt.last, maxAge, now)
+			if tt.wantErr != "" {
+				assert.EqualError(t, err, tt.wantErr)
+				return
+			}
+			assert.NoError(t, err)
+		})
+	}
+}
+
+func TestParseMaxAge(t *testing.T) {
+	tests := []struct {
+		input   string
+		want    MaxAge
+		wantErr bool
+	}{
+		{input: "", want: MaxAge{}},
+		{input: "36h", want: MaxAge{Any: 36 * time.Hour}},
+		{input: "2d", want: MaxAge{Any: 48 * time.Hour}},
+		{
+			input: "0=35d,1=8d,2=36h",
+			want:  MaxAge{Levels: map[int16]time.Duration{0: 35 * 24 * time.Hour, 1: 8 * 24 * time.Hour, 2: 36 * time.Hour}},
+		},
+		{input: "7d,2=2d", want: MaxAge{Any: 7 * 24 * time.Hour, Levels: map[int16]time.Duration{2: 48 * time.Hour}}},
+		{input: "0=abc", wantErr: true},
+		{input: "x=1d", wantErr: true},
+		{input: "-1=1d", wantErr: true},
+		{input: "0d", wantErr: true},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.input, func(t *testing.T) {
+			got, err := ParseMaxAge(tt.input)
+			if tt.wantErr {
+				assert.Error(t, err)
+				return
+			}
+			require.NoError(t, err)
+			assert.Equal(t, tt.want, got)
+		})
+	}
+}
+
+func TestLatestSnapshots(t *testing.T) {
+	at := func(name string, unix int64) zfs.SnapshotInfo {
+		return zfs.SnapshotInfo{Name: "tank/data@" + name, Created: time.Unix(unix, 0)}
+	}
+	snapshots := []zfs.SnapshotInfo{
+		at("zrb_level1_2024-01-08_00-00", 1704672000),
+		at("zrb_level0_2024-01-01_00-00", 1704067200),
+		at("auto-2024-01-20_00-00", 1705708800),
+		at("zrb_level1_2024-01-15_00-00", 1705276800),
+		at("zrb_level1_daily", 1704153600),
+	}
+
+	assert.Equal(t, []zfs.SnapshotInfo{
+		at("zrb_level0_2024-01-01_00-00", 1704067200),
+		at("zrb_level1_2024-01-15_00-00", 1705276800),
+	}, latestSnapshots(snapshots))
+	assert.Empty(t, latestSnapshots(nil))
+}
@@ -4,26 +4,42 @@ import (
 	"fmt"
 	"os"
 	"strings"
+	"time"
+	"zrb/internal/zfs"
 
 	"github.com/aws/aws-sdk-go-v2/service/s3/types"
 	"gopkg.in/yaml.v3"
 )
 
 type Task struct {
-	Name        string `yaml:"name"`
-	Description string `yaml:"description,omitempty"`
-	Pool        string `yaml:"pool"`
-	Dataset     string `yaml:"dataset"`
-	Enabled     bool   `yaml:"enabled"`
+	Name        string          `yaml:"name"`
+	Description string          `yaml:"description,omitempty"`
+	Pool        string          `yaml:"pool"`
+	Dataset     string          `yaml:"dataset"`
+	Enabled     bool            `yaml:"enabled"`
+	Send        zfs.SendOptions `yaml:"send,omitempty"`
 }
 
 type Config struct {
-	BaseDir      string   `yaml:"base_dir"`
-	AgePublicKey string   `yaml:"age_public_key"`
-	S3           S3Config `yaml:"s3"`
-	Tasks        []Task   `yaml:"tasks"`
+	BaseDir           string        `yaml:"base_dir"`
+	AgePublicKey      string        `yaml:"age_public_key"`
+	S3                S3Config      `yaml:"s3"`
+	UploadWindow      *UploadWindow `yaml:"upload_window,omitempty"`
+	Split             SplitConfig   `yaml:"split,omitempty"`
+	FreeSpaceMarginMB int64         `yaml:"free_space_margin_mb,omitempty"`
+	PostBackupHook    string        `yaml:"post_backup_hook,omitempty"`
+	Tasks             []Task        `yaml:"tasks"`
 }
 
+// SplitConfig enables adaptive part sizing when TargetCount is set; otherwise DefaultSplitSize is used.
+type SplitConfig struct {
+	TargetCount int   `yaml:"target_count,omitempty"`
+	MinSizeMB   int64 `yaml:"min_size_mb,omitempty"`
+	MaxSizeMB   int64 `yaml:"max_size_mb,omitempty"`
+}
+
+const DefaultSplitSize int64 = 3 << 30
+
 type S3Config struct {
 	Enabled      bool   `yaml:"enabled"`
 	Bucket       string `yaml:"bucket"`
@@ -39,6 +55,24 @@ type S3Config struct {
 	} `yaml:"retry,omitempty"`
 }
 
+// UploadWindow restricts uploads to a daily time range (HH:MM, local time).
+// An end before start spans midnight; Days refers to the day the window opens.
+type UploadWindow struct {
+	Start string   `yaml:"start"`
+	End   string   `yaml:"end"`
+	Days  []string `yaml:"days,omitempty"`
+}
+
+var weekdays = map[string]time.Weekday{
+	"sun": time.Sunday,
+	"mon": time.Monday,
+	"tue": time.Tuesday,
+	"wed": time.Wednesday,
+	"thu": time.Thursday,
+	"fri": time.Friday,
+	"sat": time.Saturday,
+}
+
 func Load(filename string) (*Config, error) {
 	data, err := os.ReadFile(filename)
 	if err != nil {
@@ -80,6 +114,28 @@ func (c *Config) Validate() error {
 		if t.Dataset == "" {
 			return fmt.Errorf("tasks[%d].dataset is required", i)
 		}
+		if err := ValidateDatasetName(t.Pool + "/" + t.Dataset); err != nil {
+			return fmt.Errorf("tasks[%d]: %w", i, err)
+		}
+	}
+	if c.UploadWindow != nil {
+		if err := c.UploadWindow.Validate(); err != nil {
+			return err
+		}
+	}
+	if c.FreeSpaceMarginMB < 0 {
+		return fmt.Errorf("free_space_margin_mb must not be negative")
+	}
+	if c.Split.TargetCount < 0 {
+		return fmt.Errorf("split.target_count must not be negative")
+	}
+	if c.Split.TargetCount > 0 {
+		if c.Split.MinSizeMB <= 0 {
+			return fmt.Errorf("split.min_size_mb is required when split.target_count is set")
+		}
+		if c.Split.MaxSizeMB < c.Split.MinSizeMB {
+			return fmt.Errorf("split.max_size_mb must be greater than or equal to split.min_size_mb")
+		}
 	}
 	if c.S3.Enabled {
 		if c.S3.Bucket == "" {
@@ -95,6 +151,39 @@ func (c *Config) Validate() error {
 	return nil
 }
 
+// ValidateDatasetName checks a pool/dataset name against the ZFS naming rules. Names end up in
+// local paths, so "." and ".." components are rejected as well.
+func ValidateDatasetName(name string) error {
+	components := strings.Split(name, "/")
+	if len(components) < 2 {
+		return fmt.Errorf("invalid dataset name %q: expected pool/dataset", name)
+	}
+	for _, c := range components {
+		if c == "" || c == "." || c == ".." {
+			return fmt.Errorf("invalid dataset name %q: empty or relative component", name)
+		}
+		for _, r := range c {
+			if !isDatasetNameChar(r) {
+				return fmt.Errorf("invalid dataset name %q: character %q not allowed", name, r)
+			}
+		}
+	}
+	if first := components[0][0]; !(first >= 'a' && first <= 'z' || first >= 'A' && first <= 'Z') {
+		return fmt.Errorf("invalid dataset name %q: pool name must begin with a letter", name)
+	}
+	return nil
+}
+
+func isDatasetNameChar(r rune) bool {
+	switch {
+	case r >= 'a' && r <= 'z', r >= 'A' && r <= 'Z', r >= '0' && r <= '9':
+		return true
+	case r == '_', r == '-', r == '.', r == ':', r == ' ':
+		return true
+	}
+	return false
+}
+
 func (c *Config) FindTask(name string) (*Task, error) {
 	for _, t := range c.Tasks {
 		if t.Name == name {
@@ -110,3 +199,85 @@ func (c *Config) S3RetryAttempts() int {
 	}
 	return 3
 }
+
+// SplitSize returns the part size in bytes for a send stream of the estimated size,
+// aiming for split.target_count parts within the configured bounds.
+func (c *Config) SplitSize(estimated int64) int64 {
+	if c.Split.TargetCount <= 0 {
+		return DefaultSplitSize
+	}
+	const mib = 1 << 20
+	size := (estimated + int64(c.Split.TargetCount) - 1) / int64(c.Split.TargetCount)
+	size = (size + mib - 1) / mib * mib
+	return min(max(size, c.Split.MinSizeMB*mib), c.Split.MaxSizeMB*mib)
+}
+
+func parseClock(s string) (int, error) {
+	t, err := time.Parse("15:04", s)
+	if err != nil {
+		return 0, fmt.Errorf("invalid time %q, expected HH:MM", s)
+	}
+	return t.Hour()*60 + t.Minute(), nil
+}
+
+func (w *UploadWindow) Validate() error {
+	start, err := parseClock(w.Start)
+	if err != nil {
+		return fmt.Errorf("upload_window.start: %w", err)
+	}
+	end, err := parseClock(w.End)
+	if err != nil {
+		return fmt.Errorf("upload_window.end: %w", err)
+	}
+	if start == end {
+		return fmt.Errorf("upload_window.start and upload_window.end must differ")
+	}
+	for _, d := range w.Days {
+		if _, ok := weekdays[d]; !ok {
+			return fmt.Errorf("upload_window.days: unknown day %q", d)
+		}
+	}
+	return nil
+}
+
+func (w *UploadWindow) dayAllowed(d time.Weekday) bool {
+	if len(w.Days) == 0 {
+		return true
+	}
+	for _, name := range w.Days {
+		if weekdays[name] == d {
+			return true
+		}
+	}
+	return false
+}
+
+// Contains reports whether t falls inside the window. The window must be valid.
+func (w *UploadWindow) Contains(t time.Time) bool {
+	start, _ := parseClock(w.Start)
+	end, _ := parseClock(w.End)
+	minute := t.Hour()*60 + t.Minute()
+
+	if start < end {
+		return minute >= start && minute < end && w.dayAllowed(t.Weekday())
+	}
+	if minute >= start {
+		return w.dayAllowed(t.Weekday())
+	}
+	return minute < end && w.dayAllowed((t.Weekday()+6)%7)
+}
+
+// NextOpen returns t if it is inside the window, otherwise the next time the window opens.
+func (w *UploadWindow) NextOpen(t time.Time) time.Time {
+	if w.Contains(t) {
+		return t
+	}
+	start, _ := parseClock(w.Start)
+	for i := range 8 {
+		open := time.Date(t.Year(), t.Month(), t.Day()+i, start/60, start%60, 0, 0, t.Location())
+		if open.After(t) && w.dayAllowed(open.Weekday()) {
+			return open
+		}
+	}
+	return t
+}
@@ -2,6 +2,7 @@ package config
 
 import (
 	"testing"
+	"time"
 
 	"github.com/aws/aws-sdk-go-v2/service/s3/types"
 	"github.com/stretchr/testify/assert"
@@ -114,6 +115,12 @@ func TestValidate(t *testing.T) {
 		assert.ErrorContains(t, cfg.Validate(), "tasks[0].dataset is required")
 	})
 
+	t.Run("task with path traversal dataset", func(t *testing.T) {
+		cfg := validConfig()
+		cfg.Tasks = []Task{{Name: "t", Pool: "p", Dataset: "../../etc"}}
+		assert.ErrorContains(t, cfg.Validate(), "tasks[0]: invalid dataset name")
+	})
+
 	t.Run("s3 enabled without bucket", func(t *testing.T) {
 		cfg := validConfig()
 		cfg.S3.Enabled = true
@@ -138,6 +145,30 @@ func TestValidate(t *testing.T) {
 		assert.ErrorContains(t, cfg.Validate(), "s3.storage_class.backup_data")
 	})
 
+	t.Run("invalid upload window time", func(t *testing.T) {
+		cfg := validConfig()
+		cfg.UploadWindow = &UploadWindow{Start: "25:00", End: "05:00"}
+		assert.ErrorContains(t, cfg.Validate(), "upload_window.start")
+	})
+
+	t.Run("invalid upload window day", func(t *testing.T) {
+		cfg := validConfig()
+		cfg.UploadWindow = &UploadWindow{Start: "01:00", End: "05:00", Days: []string{"monday"}}
+		assert.ErrorContains(t, cfg.Validate(), "upload_window.days")
+	})
+
+	t.Run("adaptive split without bounds", func(t *testing.T) {
+		cfg := validConfig()
+		cfg.Split.TargetCount = 100
+		assert.ErrorContains(t, cfg.Validate(), "split.min_size_mb is required")
+	})
+
+	t.Run("adaptive split with inverted bounds", func(t *testing.T) {
+		cfg := validConfig()
+		cfg.Split = SplitConfig{TargetCount: 100, MinSizeMB: 1024, MaxSizeMB: 64}
+		assert.ErrorContains(t, cfg.Validate(), "split.max_size_mb")
+	})
+
 	t.Run("valid s3 config", func(t *testing.T) {
 		cfg := validConfig()
 		cfg.S3.Enabled = true
@@ -148,6 +179,34 @@ func TestValidate(t *testing.T) {
 	})
 }
 
+func TestValidateDatasetName(t *testing.T) {
+	valid := []string{
+		"tank/data",
+		"tank/home/user",
+		"pool1/my-data_2024.v1:backup",
+		"tank/My Documents",
+	}
+	for _, name := range valid {
+		assert.NoError(t, ValidateDatasetName(name), name)
+	}
+
+	invalid := []string{
+		"tank",
+		"/tank/data",
+		"tank/data/",
+		"tank//data",
+		"tank/../../etc",
+		"tank/./data",
+		"tank/data;rm -rf /",
+		"tank/$(reboot)",
+		"tank/data@snap",
+		"1tank/data",
+	}
+	for _, name := range invalid {
+		assert.Error(t, ValidateDatasetName(name), name)
+	}
+}
+
 func TestFindTask(t *testing.T) {
 	cfg := &Config{
 		Tasks: []Task{
@@ -201,3 +260,101 @@ func TestFindTask(t *testing.T) {
 		})
 	}
 }
+
+func TestSplitSize(t *testing.T) {
+	const (
+		mib = int64(1) << 20
+		gib = int64(1) << 30
+	)
+	adaptive := SplitConfig{TargetCount: 100, MinSizeMB: 64, MaxSizeMB: 3072}
+
+	tests := []struct {
+		name      string
+		split     SplitConfig
+		estimated int64
+		want      int64
+	}{
+		{name: "fixed size when not adaptive", split: SplitConfig{}, estimated: 10 * gib, want: DefaultSplitSize},
+		{name: "10 GiB targets about 100 parts", split: adaptive, estimated: 10 * gib, want: 103 * mib},
+		{name: "1 GiB clamped to minimum", split: adaptive, estimated: gib, want: 64 * mib},
+		{name: "1 TiB clamped to maximum", split: adaptive, estimated: 1024 * gib, want: 3 * gib},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			cfg := &Config{Split: tt.split}
+			got := cfg.SplitSize(tt.estimated)
+			assert.Equal(t, tt.want, got)
+			if tt.split.TargetCount > 0 {
+				assert.GreaterOrEqual(t, got, tt.split.MinSizeMB*mib)
+				assert.LessOrEqual(t, got, tt.split.MaxSizeMB*mib)
+			}
+		})
+	}
+}
+
+func TestUploadWindow(t *testing.T) {
+	at := func(day, hour, minute int) time.Time {
+		// 2024-01-01 is a Monday
+		return time.Date(2024, 1, day, hour, minute, 0, 0, time.UTC)
+	}
+
+	tests := []struct {
+		name       string
+		window     UploadWindow
+		now        time.Time
+		wantInside bool
+		wantNext   time.Time
+	}{
+		{
+			name:       "inside daily window",
+			window:     UploadWindow{Start: "01:00", End: "05:00"},
+			now:        at(1, 2, 30),
+			wantInside: true,
+			wantNext:   at(1, 2, 30),
+		},
+		{
+			name:       "before daily window",
+			window:     UploadWindow{Start: "01:00", End: "05:00"},
+			now:        at(1, 0, 30),
+			wantInside: false,
+			wantNext:   at(1, 1, 0),
+		},
+		{
+			name:       "after daily window",
+			window:     UploadWindow{Start: "01:00", End: "05:00"},
+			now:        at(1, 5, 0),
+			wantInside: false,
+			wantNext:   at(2, 1, 0),
+		},
+		{
+			name:       "overnight window after midnight",
+			window:     UploadWindow{Start: "22:00", End: "04:00"},
+			now:        at(2, 3, 0),
+			wantInside: true,
+			wantNext:   at(2, 3, 0),
+		},
+		{
+			name:       "overnight window opened on disallowed day",
+			window:     UploadWindow{Start: "22:00", End: "04:00", Days: []string{"tue"}},
+			now:        at(2, 3, 0),
+			wantInside: false,
+			wantNext:   at(2, 22, 0),
+		},
+		{
+			name:       "weekend only window",
+			window:     UploadWindow{Start: "01:00", End: "05:00", Days: []string{"sat", "sun"}},
+			now:        at(1, 2, 0),
+			wantInside: false,
+			wantNext:   at(6, 1, 0),
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.NoError(t, tt.window.Validate())
+			assert.Equal(t, tt.wantInside, tt.window.Contains(tt.now))
+			assert.Equal(t, tt.wantNext, tt.window.NextOpen(tt.now))
+		})
+	}
+}
@@ -1,10 +1,12 @@
 package crypto
 
 import (
+	"bufio"
 	"fmt"
 	"io"
 	"log/slog"
 	"os"
+	"strings"
 
 	"filippo.io/age"
 	"github.com/zeebo/blake3"
@@ -14,16 +16,20 @@ import (
 func ProcessPart(partFile string, recipient age.Recipient) (string, string, error) {
 	slog.Info("Processing part file", "partFile", partFile)
 
-	encryptedFile := partFile + ".age"
-	if err := Encrypt(partFile, encryptedFile, recipient); err != nil {
-		return "", "", fmt.Errorf("age encryption failed: %w", err)
+	partInfo, err := os.Stat(partFile)
+	if err != nil {
+		return "", "", err
 	}
-	slog.Info("Encrypted to", "encryptedFile", encryptedFile)
 
-	blake3Hash, err := BLAKE3File(encryptedFile)
+	encryptedFile := partFile + ".age"
+	blake3Hash, err := EncryptAndHash(partFile, encryptedFile, recipient)
 	if err != nil {
-		return "", "", fmt.Errorf("BLAKE3 hash failed: %w", err)
+		return "", "", fmt.Errorf("age encryption failed: %w", err)
+	}
+	if err := VerifyEncrypted(encryptedFile, partInfo.Size()); err != nil {
+		return "", "", fmt.Errorf("encrypted file verification failed: %w", err)
 	}
+	slog.Info("Encrypted to", "encryptedFile", encryptedFile)
 	slog.Info("BLAKE3", "hash", blake3Hash)
 
 	if err := os.Remove(partFile); err != nil {
@@ -34,29 +40,131 @@ func ProcessPart(partFile string, recipient age.Recipient) (string, string, erro
 	return blake3Hash, encryptedFile, nil
 }
 
+// PrivateKeyEnv holds the age private key itself when no key file is given
+const PrivateKeyEnv = "ZRB_AGE_PRIVATE_KEY"
+
+// LoadIdentity reads an age X25519 or plugin identity from keyPath, or from PrivateKeyEnv when keyPath is empty.
+// Blank lines and # comments, as written by age-keygen, are ignored.
+func LoadIdentity(keyPath string) (age.Identity, error) {
+	var data string
+	if keyPath != "" {
+		content, err := os.ReadFile(keyPath)
+		if err != nil {
+			return nil, fmt.Errorf("failed to read private key: %w", err)
+		}
+		data = string(content)
+	} else {
+		data = os.Getenv(PrivateKeyEnv)
+		if data == "" {
+			return nil, fmt.Errorf("no private key given, use --private-key or set %s", PrivateKeyEnv)
+		}
+	}
+	return parseIdentity(data)
+}
+
+func parseIdentity(data string) (age.Identity, error) {
+	var keys []string
+	for _, line := range strings.Split(data, "\n") {
+		line = strings.TrimSpace(line)
+		if line == "" || strings.HasPrefix(line, "#") {
+			continue
+		}
+		keys = append(keys, line)
+	}
+	if len(keys) != 1 {
+		return nil, fmt.Errorf("expected exactly one private key, found %d", len(keys))
+	}
+
+	if isPluginIdentity(keys[0]) {
+		return parsePluginIdentity(keys[0])
+	}
+
+	identity, err := age.ParseX25519Identity(keys[0])
+	if err != nil {
+		return nil, fmt.Errorf("failed to parse private key: %w", err)
+	}
+	return identity, nil
+}
+
 func Encrypt(inputFile, outputFile string, recipient age.Recipient) error {
+	_, err := EncryptAndHash(inputFile, outputFile, recipient)
+	return err
+}
+
+// EncryptAndHash encrypts inputFile and returns the BLAKE3 of the encrypted output,
+// hashing the ciphertext as it is written instead of reading it back
+func EncryptAndHash(inputFile, outputFile string, recipient age.Recipient) (string, error) {
 	in, err := os.Open(inputFile)
 	if err != nil {
-		return err
+		return "", err
 	}
 	defer in.Close()
 
 	out, err := os.Create(outputFile)
 	if err != nil {
-		return err
+		return "", err
 	}
 	defer out.Close()
 
-	w, err := age.Encrypt(out, recipient)
+	hasher := blake3.New()
+	w, err := age.Encrypt(io.MultiWriter(out, hasher), recipient)
 	if err != nil {
-		return err
+		return "", err
 	}
 
 	if _, err := io.Copy(w, in); err != nil {
+		return "", err
+	}
+
+	if err := w.Close(); err != nil {
+		return "", err
+	}
+
+	return fmt.Sprintf("%x", hasher.Sum(nil)), nil
+}
+
+const (
+	ageHeaderVersion = "age-encryption.org/v1\n"
+	ageNonceSize     = 16
+	ageChunkSize     = 64 * 1024
+	ageTagSize       = 16
+)
+
+// VerifyEncrypted checks that an age file on disk has a complete header and exactly the
+// size expected for plainSize bytes of payload, catching short writes without decrypting
+func VerifyEncrypted(encryptedFile string, plainSize int64) error {
+	f, err := os.Open(encryptedFile)
+	if err != nil {
+		return err
+	}
+	defer f.Close()
+
+	info, err := f.Stat()
+	if err != nil {
 		return err
 	}
 
-	return w.Close()
+	r := bufio.NewReader(f)
+	line, err := r.ReadString('\n')
+	if err != nil || line != ageHeaderVersion {
+		return fmt.Errorf("%s is not an age file", encryptedFile)
+	}
+	headerSize := int64(len(line))
+	for !strings.HasPrefix(line, "--- ") {
+		line, err = r.ReadString('\n')
+		if err != nil {
+			return fmt.Errorf("%s has a truncated age header", encryptedFile)
+		}
+		headerSize += int64(len(line))
+	}
+
+	chunks := max((plainSize+ageChunkSize-1)/ageChunkSize, 1)
+	expected := headerSize + ageNonceSize + plainSize + chunks*ageTagSize
+	if info.Size() != expected {
+		return fmt.Errorf("%s size mismatch: expected %d bytes, got %d", encryptedFile, expected, info.Size())
+	}
+
+	return nil
 }
 
 // BLAKE3File computes the BLAKE3 hash of a file
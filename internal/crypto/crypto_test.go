@@ -0,0 +1,176 @@
+package crypto
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"testing"
+
+	"filippo.io/age"
+	"filippo.io/age/plugin"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func encryptTestFile(t *testing.T, size int) (string, int64) {
+	t.Helper()
+
+	identity, err := age.GenerateX25519Identity()
+	require.NoError(t, err)
+
+	dir := t.TempDir()
+	plainFile := filepath.Join(dir, "snapshot.part-aaaaaa")
+	require.NoError(t, os.WriteFile(plainFile, bytes.Repeat([]byte{0x5a}, size), 0o644))
+
+	encryptedFile := plainFile + ".age"
+	require.NoError(t, Encrypt(plainFile, encryptedFile, identity.Recipient()))
+
+	return encryptedFile, int64(size)
+}
+
+func TestVerifyEncrypted(t *testing.T) {
+	for _, size := range []int{0, 100, ageChunkSize, ageChunkSize + 1, 3 * ageChunkSize} {
+		encryptedFile, plainSize := encryptTestFile(t, size)
+		assert.NoError(t, VerifyEncrypted(encryptedFile, plainSize), "plaintext size %d", size)
+	}
+}
+
+func TestVerifyEncryptedTruncated(t *testing.T) {
+	encryptedFile, plainSize := encryptTestFile(t, 2*ageChunkSize)
+
+	info, err := os.Stat(encryptedFile)
+	require.NoError(t, err)
+	require.NoError(t, os.Truncate(encryptedFile, info.Size()-1000))
+
+	assert.ErrorContains(t, VerifyEncrypted(encryptedFile, plainSize), "size mismatch")
+}
+
+func TestVerifyEncryptedNotAge(t *testing.T) {
+	plainFile := filepath.Join(t.TempDir(), "plain")
+	require.NoError(t, os.WriteFile(plainFile, []byte("not encrypted"), 0o644))
+
+	assert.ErrorContains(t, VerifyEncrypted(plainFile, 13), "not an age file")
+}
+
+func TestEncryptAndHash(t *testing.T) {
+	identity, err := age.GenerateX25519Identity()
+	require.NoError(t, err)
+
+	dir := t.TempDir()
+	plain := bytes.Repeat([]byte("zrb"), ageChunkSize)
+	plainFile := filepath.Join(dir, "snapshot.part-aaaaaa")
+	require.NoError(t, os.WriteFile(plainFile, plain, 0o644))
+
+	encryptedFile := plainFile + ".age"
+	hash, err := EncryptAndHash(plainFile, encryptedFile, identity.Recipient())
+	require.NoError(t, err)
+
+	fileHash, err := BLAKE3File(encryptedFile)
+	require.NoError(t, err)
+	assert.Equal(t, fileHash, hash)
+
+	decryptedFile := filepath.Join(dir, "decrypted")
+	require.NoError(t, Decrypt(encryptedFile, decryptedFile, identity))
+	decrypted, err := os.ReadFile(decryptedFile)
+	require.NoError(t, err)
+	assert.Equal(t, plain, decrypted)
+}
+
+func TestLoadIdentity(t *testing.T) {
+	identity, err := age.GenerateX25519Identity()
+	require.NoError(t, err)
+
+	dir := t.TempDir()
+	writeKey := func(name, content string) string {
+		path := filepath.Join(dir, name)
+		require.NoError(t, os.WriteFile(path, []byte(content), 0o600))
+		return path
+	}
+
+	t.Run("zrb genkey file", func(t *testing.T) {
+		loaded, err := LoadIdentity(writeKey("zrb_private.key", identity.String()+"\n"))
+		require.NoError(t, err)
+		require.IsType(t, &age.X25519Identity{}, loaded)
+		assert.Equal(t, identity.String(), loaded.(*age.X25519Identity).String())
+	})
+
+	t.Run("age-keygen file with comments", func(t *testing.T) {
+		content := "# created: 2024-01-01T00:00:00Z\n# public key: " + identity.Recipient().String() + "\n" + identity.String() + "\n"
+		loaded, err := LoadIdentity(writeKey("age.key", content))
+		require.NoError(t, err)
+		require.IsType(t, &age.X25519Identity{}, loaded)
+		assert.Equal(t, identity.String(), loaded.(*age.X25519Identity).String())
+	})
+
+	t.Run("environment", func(t *testing.T) {
+		t.Setenv(PrivateKeyEnv, identity.String())
+		loaded, err := LoadIdentity("")
+		require.NoError(t, err)
+
+		plainFile := writeKey("plain", "zrb")
+		require.NoError(t, Encrypt(plainFile, plainFile+".age", identity.Recipient()))
+		require.NoError(t, Decrypt(plainFile+".age", plainFile+".out", loaded))
+	})
+
+	t.Run("no key given", func(t *testing.T) {
+		t.Setenv(PrivateKeyEnv, "")
+		_, err := LoadIdentity("")
+		assert.ErrorContains(t, err, "no private key given")
+	})
+
+	t.Run("malformed", func(t *testing.T) {
+		_, err := LoadIdentity(writeKey("bad.key", "AGE-SECRET-KEY-NOTAKEY\n"))
+		assert.ErrorContains(t, err, "failed to parse private key")
+	})
+
+	t.Run("multiple keys", func(t *testing.T) {
+		_, err := LoadIdentity(writeKey("two.key", identity.String()+"\n"+identity.String()+"\n"))
+		assert.ErrorContains(t, err, "expected exactly one private key, found 2")
+	})
+
+	t.Run("missing file", func(t *testing.T) {
+		_, err := LoadIdentity(filepath.Join(dir, "missing.key"))
+		assert.ErrorContains(t, err, "failed to read private key")
+	})
+}
+
+func TestParseRecipient(t *testing.T) {
+	identity, err := age.GenerateX25519Identity()
+	require.NoError(t, err)
+
+	t.Run("x25519", func(t *testing.T) {
+		recipient, err := ParseRecipient(identity.Recipient().String())
+		require.NoError(t, err)
+		assert.IsType(t, &age.X25519Recipient{}, recipient)
+	})
+
+	t.Run("plugin", func(t *testing.T) {
+		binDir := t.TempDir()
+		require.NoError(t, os.WriteFile(filepath.Join(binDir, "age-plugin-zrbtest"), []byte("#!/bin/sh\nexit 1\n"), 0o755))
+		t.Setenv("PATH", binDir)
+
+		recipient, err := ParseRecipient(plugin.EncodeRecipient("zrbtest", []byte{1, 2, 3}))
+		require.NoError(t, err)
+		assert.IsType(t, &plugin.Recipient{}, recipient)
+	})
+
+	t.Run("plugin not installed", func(t *testing.T) {
+		t.Setenv("PATH", t.TempDir())
+
+		_, err := ParseRecipient(plugin.EncodeRecipient("zrbtest", []byte{1, 2, 3}))
+		assert.ErrorContains(t, err, "age-plugin-zrbtest is required for this key but was not found in PATH")
+	})
+
+	t.Run("invalid", func(t *testing.T) {
+		_, err := ParseRecipient("age1notarecipient")
+		assert.ErrorContains(t, err, "invalid age recipient")
+	})
+}
+
+func TestLoadPluginIdentity(t *testing.T) {
+	t.Setenv("PATH", t.TempDir())
+	t.Setenv(PrivateKeyEnv, "# public key: age1yubikey1...\n"+plugin.EncodeIdentity("zrbtest", []byte{1, 2, 3})+"\n")
+
+	_, err := LoadIdentity("")
+	assert.ErrorContains(t, err, "age-plugin-zrbtest is required for this key")
+}
@@ -0,0 +1,68 @@
+package crypto
+
+import (
+	"fmt"
+	"os"
+	"os/exec"
+	"strings"
+
+	"filippo.io/age"
+	"filippo.io/age/plugin"
+)
+
+// pluginUI forwards plugin messages to stderr. zrb runs unattended, so plugins that
+// need to ask for a value or a confirmation fail instead of blocking.
+var pluginUI = &plugin.ClientUI{
+	DisplayMessage: func(name, message string) error {
+		fmt.Fprintf(os.Stderr, "age-plugin-%s: %s\n", name, message)
+		return nil
+	},
+	RequestValue: func(name, prompt string, _ bool) (string, error) {
+		return "", fmt.Errorf("age-plugin-%s requested input (%s), interactive plugin prompts are not supported", name, prompt)
+	},
+	Confirm: func(name, prompt, _, _ string) (bool, error) {
+		return false, fmt.Errorf("age-plugin-%s requested confirmation (%s), interactive plugin prompts are not supported", name, prompt)
+	},
+	WaitTimer: func(name string) {
+		fmt.Fprintf(os.Stderr, "age-plugin-%s: waiting for the plugin, touch the hardware key if it is flashing\n", name)
+	},
+}
+
+// ParseRecipient parses an X25519 recipient or a plugin recipient such as age1yubikey1...
+func ParseRecipient(s string) (age.Recipient, error) {
+	if recipient, err := age.ParseX25519Recipient(s); err == nil {
+		return recipient, nil
+	}
+
+	recipient, err := plugin.NewRecipient(s, pluginUI)
+	if err != nil {
+		return nil, fmt.Errorf("invalid age recipient: %w", err)
+	}
+	if err := checkPluginInstalled(recipient.Name()); err != nil {
+		return nil, err
+	}
+	return recipient, nil
+}
+
+func parsePluginIdentity(s string) (age.Identity, error) {
+	identity, err := plugin.NewIdentity(s, pluginUI)
+	if err != nil {
+		return nil, fmt.Errorf("failed to parse private key: %w", err)
+	}
+	if err := checkPluginInstalled(identity.Name()); err != nil {
+		return nil, err
+	}
+	return identity, nil
+}
+
+func isPluginIdentity(s string) bool {
+	return strings.HasPrefix(s, "AGE-PLUGIN-")
+}
+
+func checkPluginInstalled(name string) error {
+	binary := "age-plugin-" + name
+	if _, err := exec.LookPath(binary); err != nil {
+		return fmt.Errorf("%s is required for this key but was not found in PATH: %w", binary, err)
+	}
+	return nil
+}
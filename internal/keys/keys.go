@@ -5,7 +5,6 @@ import (
 	"fmt"
 	"os"
 	"path/filepath"
-	"strings"
 	"time"
 	"zrb/internal/config"
 	"zrb/internal/crypto"
@@ -59,25 +58,24 @@ func Test(_ context.Context, configPath, privateKeyPath string) error {
 		return fmt.Errorf("failed to load config: %w", err)
 	}
 
-	recipient, err := age.ParseX25519Recipient(cfg.AgePublicKey)
+	recipient, err := crypto.ParseRecipient(cfg.AgePublicKey)
 	if err != nil {
 		return fmt.Errorf("failed to parse public key from config: %w", err)
 	}
 
 	fmt.Printf("Public key from config: %s\n", cfg.AgePublicKey)
 
-	privateKeyData, err := os.ReadFile(privateKeyPath)
+	identity, err := crypto.LoadIdentity(privateKeyPath)
 	if err != nil {
-		return fmt.Errorf("failed to read private key: %w", err)
+		return err
 	}
 
-	identity, err := age.ParseX25519Identity(strings.TrimSpace(string(privateKeyData)))
-	if err != nil {
-		return fmt.Errorf("failed to parse private key: %w", err)
+	if privateKeyPath != "" {
+		fmt.Printf("Private key loaded from: %s\n", privateKeyPath)
+	} else {
+		fmt.Printf("Private key loaded from: $%s\n", crypto.PrivateKeyEnv)
 	}
 
-	fmt.Printf("Private key loaded from: %s\n", privateKeyPath)
-
 	tempDir, err := os.MkdirTemp("", "zrb_key_test_*")
 	if err != nil {
 		return fmt.Errorf("failed to create temp directory: %w", err)
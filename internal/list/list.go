@@ -53,6 +53,24 @@ func Run(ctx context.Context, configPath, taskName string, filterLevel int16, so
 		return err
 	}
 
+	output := Output{
+		Task:    taskName,
+		Pool:    task.Pool,
+		Dataset: task.Dataset,
+		Source:  source,
+		Backups: []Info{},
+	}
+
+	if source == "history" {
+		historyPath := filepath.Join(cfg.BaseDir, "run", task.Pool, task.Dataset, "backup_history.yaml")
+		entries, err := manifest.ReadHistory(historyPath)
+		if err != nil && !os.IsNotExist(err) {
+			return fmt.Errorf("failed to read backup history from %s: %w", historyPath, err)
+		}
+		output.Backups = historyInfos(entries, filterLevel)
+		return writeOutput(&output)
+	}
+
 	var lastBackup *manifest.Last
 	var lastPath string
 
@@ -97,14 +115,6 @@ func Run(ctx context.Context, configPath, taskName string, filterLevel int16, so
 		return fmt.Errorf("failed to read backup manifest from %s: %w", lastPath, err)
 	}
 
-	output := Output{
-		Task:    taskName,
-		Pool:    task.Pool,
-		Dataset: task.Dataset,
-		Source:  source,
-		Backups: []Info{},
-	}
-
 	for level, ref := range lastBackup.BackupLevels {
 		if ref == nil {
 			continue
@@ -123,7 +133,11 @@ func Run(ctx context.Context, configPath, taskName string, filterLevel int16, so
 
 		if ref.Manifest != "" {
 			if m, err := manifest.Read(ref.Manifest); err == nil {
-				estimatedSizeGB = len(m.Parts) * 3
+				splitSize := m.SplitSize
+				if splitSize == 0 {
+					splitSize = config.DefaultSplitSize
+				}
+				estimatedSizeGB = int(int64(len(m.Parts)) * splitSize >> 30)
 			}
 		}
 
@@ -154,6 +168,39 @@ func Run(ctx context.Context, configPath, taskName string, filterLevel int16, so
 		output.Backups = append(output.Backups, info)
 	}
 
+	return writeOutput(&output)
+}
+
+// historyInfos lists every completed backup recorded in the history, oldest first
+func historyInfos(entries []manifest.HistoryEntry, filterLevel int16) []Info {
+	infos := []Info{}
+	for _, e := range entries {
+		if filterLevel >= 0 && e.BackupLevel != filterLevel {
+			continue
+		}
+
+		backupType := "full"
+		if e.BackupLevel > 0 {
+			backupType = "incremental"
+		}
+
+		infos = append(infos, Info{
+			Level:           e.BackupLevel,
+			Type:            backupType,
+			Datetime:        e.Datetime,
+			DatetimeStr:     time.Unix(e.Datetime, 0).Format("2006-01-02 15:04:05"),
+			Snapshot:        e.TargetSnapshot,
+			ParentSnapshot:  e.ParentSnapshot,
+			Blake3Hash:      e.Blake3Hash,
+			PartsCount:      e.Parts,
+			EstimatedSizeGB: int(e.Bytes >> 30),
+			S3Path:          e.S3Path,
+		})
+	}
+	return infos
+}
+
+func writeOutput(output *Output) error {
 	output.Summary.TotalBackups = len(output.Backups)
 	for _, backup := range output.Backups {
 		if backup.Type == "full" {
@@ -0,0 +1,36 @@
+package list
+
+import (
+	"testing"
+	"zrb/internal/manifest"
+
+	"github.com/stretchr/testify/assert"
+)
+
+func TestHistoryInfos(t *testing.T) {
+	entries := []manifest.HistoryEntry{
+		{Datetime: 1704067200, BackupLevel: 0, TargetSnapshot: "tank/data@zrb_level0_2024-01-01_00-00", Parts: 3, Bytes: 8 << 30, S3Path: "tank/data/level0/20240101"},
+		{Datetime: 1704672000, BackupLevel: 1, TargetSnapshot: "tank/data@zrb_level1_2024-01-08_00-00", ParentSnapshot: "tank/data@zrb_level0_2024-01-01_00-00", Parts: 1},
+		{Datetime: 1706745600, BackupLevel: 0, TargetSnapshot: "tank/data@zrb_level0_2024-02-01_00-00", Parts: 3},
+	}
+
+	t.Run("all levels in order", func(t *testing.T) {
+		infos := historyInfos(entries, -1)
+		assert.Len(t, infos, 3)
+		assert.Equal(t, "full", infos[0].Type)
+		assert.Equal(t, 8, infos[0].EstimatedSizeGB)
+		assert.Equal(t, "incremental", infos[1].Type)
+		assert.Equal(t, "tank/data@zrb_level0_2024-01-01_00-00", infos[1].ParentSnapshot)
+		assert.Equal(t, "tank/data@zrb_level0_2024-02-01_00-00", infos[2].Snapshot)
+	})
+
+	t.Run("filtered by level", func(t *testing.T) {
+		infos := historyInfos(entries, 0)
+		assert.Len(t, infos, 2)
+		assert.Equal(t, 3, infos[1].PartsCount)
+	})
+
+	t.Run("no history", func(t *testing.T) {
+		assert.Empty(t, historyInfos(nil, -1))
+	})
+}
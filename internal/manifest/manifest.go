@@ -2,6 +2,9 @@ package manifest
 
 import (
 	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
 	"os"
 	"os/exec"
 	"strings"
@@ -112,5 +115,52 @@ func ReadState(filename string) (*State, error) {
 	if err := yaml.Unmarshal(data, &state); err != nil {
 		return nil, err
 	}
+	for index, p := range state.PartsCompleted {
+		if p.Index == "" {
+			p.Index = index
+			state.PartsCompleted[index] = p
+		}
+	}
 	return &state, nil
 }
+
+// AppendHistory appends an entry to the history file as a separate YAML document,
+// so earlier entries are never rewritten
+func AppendHistory(filename string, entry *HistoryEntry) error {
+	data, err := yaml.Marshal(entry)
+	if err != nil {
+		return err
+	}
+
+	f, err := os.OpenFile(filename, os.O_APPEND|os.O_CREATE|os.O_WRONLY, 0o644)
+	if err != nil {
+		return err
+	}
+	if _, err := f.Write(append([]byte("---\n"), data...)); err != nil {
+		f.Close()
+		return err
+	}
+	return f.Close()
+}
+
+// ReadHistory returns all history entries, oldest first
+func ReadHistory(filename string) ([]HistoryEntry, error) {
+	f, err := os.Open(filename)
+	if err != nil {
+		return nil, err
+	}
+	defer f.Close()
+
+	var entries []HistoryEntry
+	decoder := yaml.NewDecoder(f)
+	for {
+		var entry HistoryEntry
+		if err := decoder.Decode(&entry); err != nil {
+			if errors.Is(err, io.EOF) {
+				return entries, nil
+			}
+			return nil, fmt.Errorf("failed to parse history entry %d: %w", len(entries)+1, err)
+		}
+		entries = append(entries, entry)
+	}
+}
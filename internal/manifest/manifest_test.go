@@ -0,0 +1,179 @@
+package manifest
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestStateBytesCompleted(t *testing.T) {
+	statePath := filepath.Join(t.TempDir(), "backup_state.yaml")
+
+	state := &State{TaskName: "t1", StreamBytes: 5000, PartsCompleted: make(map[string]PartInfo)}
+	assert.Equal(t, int64(0), state.BytesCompleted())
+
+	for _, p := range []PartInfo{
+		{Index: "aaaaaa", Blake3Hash: "h1", Size: 3000},
+		{Index: "aaaaab", Blake3Hash: "h2", Size: 2000},
+		{Index: "aaaaac", Blake3Hash: "h3", Size: 500},
+	} {
+		state.PartsCompleted[p.Index] = p
+		require.NoError(t, WriteState(statePath, state))
+	}
+
+	loaded, err := ReadState(statePath)
+	require.NoError(t, err)
+	assert.Equal(t, int64(5500), loaded.BytesCompleted())
+	assert.Equal(t, int64(5000), loaded.StreamBytes)
+	assert.Equal(t, state.PartsCompleted, loaded.PartsCompleted)
+}
+
+func TestReadStateLegacyParts(t *testing.T) {
+	statePath := filepath.Join(t.TempDir(), "backup_state.yaml")
+	legacy := `task_name: t1
+backup_level: 0
+target_snapshot: tank/data@zrb_level0_2024-01-01_00-00
+output_dir: /var/zrb/task/tank/data/level0/20240101
+blake3_hash: stream
+parts_completed:
+  aaaaaa: h1
+  aaaaab: h2
+`
+	require.NoError(t, os.WriteFile(statePath, []byte(legacy), 0o644))
+
+	state, err := ReadState(statePath)
+	require.NoError(t, err)
+	assert.Equal(t, map[string]PartInfo{
+		"aaaaaa": {Index: "aaaaaa", Blake3Hash: "h1"},
+		"aaaaab": {Index: "aaaaab", Blake3Hash: "h2"},
+	}, state.PartsCompleted)
+	assert.NoError(t, state.Validate())
+}
+
+func TestLastParentRef(t *testing.T) {
+	last := &Last{
+		Pool:    "tank",
+		Dataset: "data",
+		BackupLevels: []*Ref{
+			{Snapshot: "tank/data@zrb_level0_2024-01-01_00-00"},
+			{Snapshot: "tank/data@zrb_level1_2024-01-08_00-00"},
+		},
+	}
+
+	tests := []struct {
+		name    string
+		level   int16
+		want    string
+		wantErr string
+	}{
+		{name: "level 1 uses level 0", level: 1, want: "tank/data@zrb_level0_2024-01-01_00-00"},
+		{name: "level 2 uses level 1", level: 2, want: "tank/data@zrb_level1_2024-01-08_00-00"},
+		{name: "missing predecessor", level: 3, wantErr: "no level 2 backup found"},
+		{name: "full backup", level: 0, wantErr: "has no parent"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			ref, err := last.ParentRef(tt.level)
+			if tt.wantErr != "" {
+				assert.ErrorContains(t, err, tt.wantErr)
+				return
+			}
+			require.NoError(t, err)
+			assert.Equal(t, tt.want, ref.Snapshot)
+		})
+	}
+
+	t.Run("pruned predecessor", func(t *testing.T) {
+		pruned := &Last{BackupLevels: []*Ref{{Snapshot: "a"}, nil}}
+		_, err := pruned.ParentRef(2)
+		assert.ErrorContains(t, err, "no level 1 backup found")
+	})
+}
+
+func TestStateValidate(t *testing.T) {
+	healthy := func() *State {
+		return &State{
+			TaskName:       "t1",
+			BackupLevel:    1,
+			TargetSnapshot: "tank/data@zrb_level1_2024-01-08_00-00",
+			ParentSnapshot: "tank/data@zrb_level0_2024-01-01_00-00",
+			OutputDir:      "/var/zrb/task/tank/data/level1/20240108",
+			Blake3Hash:     "abc",
+			PartsCompleted: map[string]PartInfo{
+				"aaaaaa": {Index: "aaaaaa", Blake3Hash: "h1", Size: 100},
+			},
+			ManifestCreated: true,
+		}
+	}
+
+	assert.NoError(t, healthy().Validate())
+
+	tests := []struct {
+		name    string
+		mutate  func(s *State)
+		wantErr string
+	}{
+		{name: "no task name", mutate: func(s *State) { s.TaskName = "" }, wantErr: "task name is empty"},
+		{name: "negative level", mutate: func(s *State) { s.BackupLevel = -1 }, wantErr: "backup level -1 is negative"},
+		{name: "no target", mutate: func(s *State) { s.TargetSnapshot = "" }, wantErr: "target snapshot is empty"},
+		{name: "level 0 with parent", mutate: func(s *State) { s.BackupLevel = 0 }, wantErr: "level 0 backup has parent snapshot"},
+		{name: "incremental without parent", mutate: func(s *State) { s.ParentSnapshot = "" }, wantErr: "level 1 backup has no parent snapshot"},
+		{name: "no output dir", mutate: func(s *State) { s.OutputDir = "" }, wantErr: "output directory is empty"},
+		{name: "no stream hash", mutate: func(s *State) { s.Blake3Hash = "" }, wantErr: "stream BLAKE3 hash is empty"},
+		{
+			name:    "part index mismatch",
+			mutate:  func(s *State) { s.PartsCompleted["aaaaab"] = PartInfo{Index: "aaaaac", Blake3Hash: "h2"} },
+			wantErr: `part aaaaab is recorded with index "aaaaac"`,
+		},
+		{
+			name:    "part without hash",
+			mutate:  func(s *State) { s.PartsCompleted["aaaaaa"] = PartInfo{Index: "aaaaaa"} },
+			wantErr: "part aaaaaa has no BLAKE3 hash",
+		},
+		{
+			name:    "uploaded before created",
+			mutate:  func(s *State) { s.ManifestCreated, s.ManifestUploaded = false, true },
+			wantErr: "manifest uploaded but not created",
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			state := healthy()
+			tt.mutate(state)
+			assert.ErrorContains(t, state.Validate(), tt.wantErr)
+		})
+	}
+
+	t.Run("reports all violations", func(t *testing.T) {
+		err := (&State{TaskName: "t1"}).Validate()
+		require.Error(t, err)
+		assert.ErrorContains(t, err, "target snapshot is empty")
+		assert.ErrorContains(t, err, "output directory is empty")
+		assert.ErrorContains(t, err, "stream BLAKE3 hash is empty")
+	})
+}
+
+func TestHistory(t *testing.T) {
+	historyPath := filepath.Join(t.TempDir(), "backup_history.yaml")
+
+	_, err := ReadHistory(historyPath)
+	assert.True(t, os.IsNotExist(err))
+
+	entries := []HistoryEntry{
+		{Datetime: 1704067200, TaskName: "t1", BackupLevel: 0, TargetSnapshot: "tank/data@zrb_level0_2024-01-01_00-00", Parts: 3, Bytes: 5500, StreamBytes: 5000},
+		{Datetime: 1704672000, TaskName: "t1", BackupLevel: 1, TargetSnapshot: "tank/data@zrb_level1_2024-01-08_00-00", ParentSnapshot: "tank/data@zrb_level0_2024-01-01_00-00", Parts: 1, Bytes: 100},
+		{Datetime: 1705276800, TaskName: "t1", BackupLevel: 1, TargetSnapshot: "tank/data@zrb_level1_2024-01-15_00-00", ParentSnapshot: "tank/data@zrb_level0_2024-01-01_00-00", Parts: 1, Bytes: 200},
+	}
+	for i := range entries {
+		require.NoError(t, AppendHistory(historyPath, &entries[i]))
+	}
+
+	loaded, err := ReadHistory(historyPath)
+	require.NoError(t, err)
+	assert.Equal(t, entries, loaded)
+}
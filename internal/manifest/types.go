@@ -1,8 +1,27 @@
 package manifest
 
+import (
+	"errors"
+	"fmt"
+
+	"gopkg.in/yaml.v3"
+)
+
 type PartInfo struct {
 	Index      string `yaml:"index"`
 	Blake3Hash string `yaml:"blake3_hash"`
+	Size       int64  `yaml:"size"` // encrypted size in bytes
+}
+
+// UnmarshalYAML also accepts the plain hash string that parts_completed held before part sizes
+// were recorded, so a backup interrupted before an upgrade still resumes. Such parts have Size 0.
+func (p *PartInfo) UnmarshalYAML(value *yaml.Node) error {
+	if value.Kind == yaml.ScalarNode {
+		*p = PartInfo{Blake3Hash: value.Value}
+		return nil
+	}
+	type plain PartInfo
+	return value.Decode((*plain)(p))
 }
 
 type SystemInfo struct {
@@ -24,6 +43,7 @@ type Backup struct {
 	ParentSnapshot string     `yaml:"parent_snapshot"`
 	AgePublicKey   string     `yaml:"age_public_key"`
 	Blake3Hash     string     `yaml:"blake3_hash"`
+	SplitSize      int64      `yaml:"split_size"`
 	Parts          []PartInfo `yaml:"parts"`
 	TargetS3Path   string     `yaml:"target_s3_path"`
 	ParentS3Path   string     `yaml:"parent_s3_path"`
@@ -43,15 +63,95 @@ type Last struct {
 	BackupLevels []*Ref `yaml:"backup_levels"`
 }
 
+// HistoryEntry records one completed backup in the append-only history file
+type HistoryEntry struct {
+	Datetime        int64  `yaml:"datetime"`
+	TaskName        string `yaml:"task_name"`
+	Pool            string `yaml:"pool"`
+	Dataset         string `yaml:"dataset"`
+	BackupLevel     int16  `yaml:"backup_level"`
+	TargetSnapshot  string `yaml:"target_snapshot"`
+	ParentSnapshot  string `yaml:"parent_snapshot"`
+	Blake3Hash      string `yaml:"blake3_hash"`
+	Parts           int    `yaml:"parts"`
+	Bytes           int64  `yaml:"bytes"`            // total encrypted size
+	StreamBytes     int64  `yaml:"stream_bytes"`     // raw zfs send stream size
+	DurationSeconds int64  `yaml:"duration_seconds"` // of the run that completed the backup
+	S3Path          string `yaml:"s3_path"`
+}
+
 type State struct {
-	TaskName         string            `yaml:"task_name"`
-	BackupLevel      int16             `yaml:"backup_level"`
-	TargetSnapshot   string            `yaml:"target_snapshot"`
-	ParentSnapshot   string            `yaml:"parent_snapshot"`
-	OutputDir        string            `yaml:"output_dir"`
-	Blake3Hash       string            `yaml:"blake3_hash"`
-	PartsCompleted   map[string]string `yaml:"parts_completed"`
-	ManifestCreated  bool              `yaml:"manifest_created"`
-	ManifestUploaded bool              `yaml:"manifest_uploaded"`
-	LastUpdated      int64             `yaml:"last_updated"`
+	TaskName         string              `yaml:"task_name"`
+	BackupLevel      int16               `yaml:"backup_level"`
+	TargetSnapshot   string              `yaml:"target_snapshot"`
+	ParentSnapshot   string              `yaml:"parent_snapshot"`
+	OutputDir        string              `yaml:"output_dir"`
+	Blake3Hash       string              `yaml:"blake3_hash"`
+	SplitSize        int64               `yaml:"split_size"`
+	StreamBytes      int64               `yaml:"stream_bytes"`
+	StartedAt        int64               `yaml:"started_at"` // when part processing began
+	PartsCompleted   map[string]PartInfo `yaml:"parts_completed"`
+	ManifestCreated  bool                `yaml:"manifest_created"`
+	ManifestUploaded bool                `yaml:"manifest_uploaded"`
+	LastUpdated      int64               `yaml:"last_updated"`
+}
+
+// Validate reports every invariant a persisted state violates, so a corrupted or
+// hand-edited state file is rejected before resuming from it
+func (s *State) Validate() error {
+	var errs []error
+	if s.TaskName == "" {
+		errs = append(errs, errors.New("task name is empty"))
+	}
+	if s.BackupLevel < 0 {
+		errs = append(errs, fmt.Errorf("backup level %d is negative", s.BackupLevel))
+	}
+	if s.TargetSnapshot == "" {
+		errs = append(errs, errors.New("target snapshot is empty"))
+	}
+	if s.BackupLevel == 0 && s.ParentSnapshot != "" {
+		errs = append(errs, fmt.Errorf("level 0 backup has parent snapshot %s", s.ParentSnapshot))
+	}
+	if s.BackupLevel > 0 && s.ParentSnapshot == "" {
+		errs = append(errs, fmt.Errorf("level %d backup has no parent snapshot", s.BackupLevel))
+	}
+	if s.OutputDir == "" {
+		errs = append(errs, errors.New("output directory is empty"))
+	}
+	if s.Blake3Hash == "" {
+		errs = append(errs, errors.New("stream BLAKE3 hash is empty"))
+	}
+	for index, p := range s.PartsCompleted {
+		if p.Index != index {
+			errs = append(errs, fmt.Errorf("part %s is recorded with index %q", index, p.Index))
+		}
+		if p.Blake3Hash == "" {
+			errs = append(errs, fmt.Errorf("part %s has no BLAKE3 hash", index))
+		}
+	}
+	if s.ManifestUploaded && !s.ManifestCreated {
+		errs = append(errs, errors.New("manifest uploaded but not created"))
+	}
+	return errors.Join(errs...)
+}
+
+// BytesCompleted returns the total encrypted size of all completed parts
+func (s *State) BytesCompleted() int64 {
+	var total int64
+	for _, p := range s.PartsCompleted {
+		total += p.Size
+	}
+	return total
+}
+
+// ParentRef returns the reference a backup at the given level is based on,
+// which is always the most recent backup of the immediately preceding level
+func (l *Last) ParentRef(level int16) (*Ref, error) {
+	if level <= 0 {
+		return nil, fmt.Errorf("level %d backup has no parent", level)
+	}
+	if int(level) > len(l.BackupLevels) || l.BackupLevels[level-1] == nil {
+		return nil, fmt.Errorf("no level %d backup found to base level %d on", level-1, level)
+	}
+	return l.BackupLevels[level-1], nil
 }
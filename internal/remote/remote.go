@@ -2,10 +2,14 @@ package remote
 
 import (
 	"context"
+	"errors"
 	"fmt"
 	"log/slog"
+	"mime"
 	"os"
 	"path/filepath"
+	"strings"
+	"time"
 
 	"github.com/aws/aws-sdk-go-v2/aws"
 	awsconfig "github.com/aws/aws-sdk-go-v2/config"
@@ -23,6 +27,8 @@ type ObjectInfo struct {
 type Backend interface {
 	Upload(ctx context.Context, localPath, remotePath, checksumHash string, backupLevel int16) error
 	Head(ctx context.Context, remotePath string) (*ObjectInfo, error)
+	List(ctx context.Context, remoteDir string) ([]string, error)
+	DeleteAll(ctx context.Context, remotePaths []string) error
 	VerifyCredentials(ctx context.Context) error
 }
 
@@ -113,6 +119,26 @@ func (s *S3) Download(ctx context.Context, remotePath, localPath string) error {
 	return nil
 }
 
+// maxPresignExpiry is the longest validity SigV4 allows for a presigned URL
+const maxPresignExpiry = 7 * 24 * time.Hour
+
+// PresignDownload returns a URL that downloads the object without credentials until expires has passed
+func (s *S3) PresignDownload(ctx context.Context, remotePath string, expires time.Duration) (string, error) {
+	if expires <= 0 || expires > maxPresignExpiry {
+		return "", fmt.Errorf("presigned URL expiry must be between 0 and %s, got %s", maxPresignExpiry, expires)
+	}
+
+	key := filepath.ToSlash(filepath.Join(s.prefix, remotePath))
+	req, err := s3.NewPresignClient(s.client).PresignGetObject(ctx, &s3.GetObjectInput{
+		Bucket: aws.String(s.bucket),
+		Key:    aws.String(key),
+	}, s3.WithPresignExpires(expires))
+	if err != nil {
+		return "", fmt.Errorf("failed to presign %s: %w", key, err)
+	}
+	return req.URL, nil
+}
+
 func (s *S3) Upload(ctx context.Context, localPath, remotePath, checksumHash string, backupLevel int16) error {
 	var levelTag string
 	if backupLevel < 0 {
@@ -130,12 +156,13 @@ func (s *S3) Upload(ctx context.Context, localPath, remotePath, checksumHash str
 	key := filepath.ToSlash(filepath.Join(s.prefix, remotePath))
 
 	input := &s3.PutObjectInput{
-		Bucket:       aws.String(s.bucket),
-		Key:          aws.String(key),
-		Body:         file,
-		StorageClass: s.storageClass,
-		Tagging:      aws.String("backup-level=" + levelTag),
-		Metadata:     map[string]string{"blake3": checksumHash},
+		Bucket:             aws.String(s.bucket),
+		Key:                aws.String(key),
+		Body:               file,
+		StorageClass:       s.storageClass,
+		Tagging:            aws.String("backup-level=" + levelTag),
+		Metadata:           map[string]string{"blake3": checksumHash},
+		ContentDisposition: aws.String(contentDisposition(remotePath)),
 	}
 
 	_, err = s.uploader.Upload(ctx, input)
@@ -168,6 +195,67 @@ func (s *S3) Head(ctx context.Context, remotePath string) (*ObjectInfo, error) {
 	return info, nil
 }
 
+// List returns the paths of all objects below remoteDir, relative to the configured prefix like the other methods
+func (s *S3) List(ctx context.Context, remoteDir string) ([]string, error) {
+	keyPrefix := filepath.ToSlash(filepath.Join(s.prefix, remoteDir)) + "/"
+
+	var remotePaths []string
+	paginator := s3.NewListObjectsV2Paginator(s.client, &s3.ListObjectsV2Input{
+		Bucket: aws.String(s.bucket),
+		Prefix: aws.String(keyPrefix),
+	})
+	for paginator.HasMorePages() {
+		page, err := paginator.NextPage(ctx)
+		if err != nil {
+			return nil, fmt.Errorf("failed to list objects under %s: %w", keyPrefix, err)
+		}
+		for _, obj := range page.Contents {
+			remotePaths = append(remotePaths, filepath.Join(remoteDir, strings.TrimPrefix(aws.ToString(obj.Key), keyPrefix)))
+		}
+	}
+	return remotePaths, nil
+}
+
+const maxDeleteBatch = 1000 // DeleteObjects limit
+
+// DeleteAll removes objects in batches, deleting an object that does not exist is not an error.
+// Objects S3 fails to delete are reported together after all batches were tried.
+func (s *S3) DeleteAll(ctx context.Context, remotePaths []string) error {
+	var errs []error
+	for _, batch := range batches(remotePaths, maxDeleteBatch) {
+		objects := make([]types.ObjectIdentifier, len(batch))
+		for i, remotePath := range batch {
+			objects[i] = types.ObjectIdentifier{Key: aws.String(filepath.ToSlash(filepath.Join(s.prefix, remotePath)))}
+		}
+
+		output, err := s.client.DeleteObjects(ctx, &s3.DeleteObjectsInput{
+			Bucket: aws.String(s.bucket),
+			Delete: &types.Delete{Objects: objects, Quiet: aws.Bool(true)},
+		})
+		if err != nil {
+			return fmt.Errorf("failed to delete objects: %w", err)
+		}
+
+		for _, e := range output.Errors {
+			errs = append(errs, fmt.Errorf("failed to delete object %s: %s", aws.ToString(e.Key), aws.ToString(e.Message)))
+		}
+		slog.Info("Deleted from S3", "bucket", s.bucket, "count", len(batch)-len(output.Errors))
+	}
+	return errors.Join(errs...)
+}
+
+func batches(items []string, size int) [][]string {
+	var result [][]string
+	for len(items) > size {
+		result = append(result, items[:size])
+		items = items[size:]
+	}
+	if len(items) > 0 {
+		result = append(result, items)
+	}
+	return result
+}
+
 func (s *S3) VerifyCredentials(ctx context.Context) error {
 	slog.Info("Verifying AWS credentials and bucket access", "bucket", s.bucket)
 
@@ -182,6 +270,23 @@ func (s *S3) VerifyCredentials(ctx context.Context) error {
 	return nil
 }
 
+// contentDisposition names downloaded objects after their whole key below data/ or manifests/,
+// e.g. tank_data_level0_20240101_snapshot.part-aaaaaa.age, since the base names repeat across backups
+func contentDisposition(remotePath string) string {
+	parts := strings.Split(filepath.ToSlash(remotePath), "/")
+	if len(parts) > 1 && (parts[0] == "data" || parts[0] == "manifests") {
+		parts = parts[1:]
+	}
+	return mime.FormatMediaType("attachment", map[string]string{"filename": strings.Join(parts, "_")})
+}
+
+// IsNotFound reports whether err means the object does not exist
+func IsNotFound(err error) bool {
+	var notFound *types.NotFound
+	var noSuchKey *types.NoSuchKey
+	return errors.As(err, &notFound) || errors.As(err, &noSuchKey)
+}
+
 func ValidateStorageClass(storageClass string) error {
 	if storageClass == "GLACIER" || storageClass == "DEEP_ARCHIVE" {
 		return fmt.Errorf("storage class %s is not immediately accessible (requires restore)", storageClass)
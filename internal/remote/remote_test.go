@@ -1,9 +1,16 @@
 package remote
 
 import (
+	"context"
+	"errors"
+	"fmt"
+	"strings"
 	"testing"
+	"time"
 
+	"github.com/aws/aws-sdk-go-v2/service/s3/types"
 	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
 )
 
 func TestValidateStorageClass(t *testing.T) {
@@ -62,3 +69,66 @@ func TestValidateStorageClass(t *testing.T) {
 		})
 	}
 }
+
+func TestIsNotFound(t *testing.T) {
+	assert.True(t, IsNotFound(fmt.Errorf("failed to head object: %w", &types.NotFound{})))
+	assert.True(t, IsNotFound(&types.NoSuchKey{}))
+	assert.False(t, IsNotFound(errors.New("access denied")))
+	assert.False(t, IsNotFound(nil))
+}
+
+func TestContentDisposition(t *testing.T) {
+	tests := []struct {
+		remotePath string
+		want       string
+	}{
+		{"data/tank/data/level0/20240101/snapshot.part-aaaaaa.age", "attachment; filename=tank_data_level0_20240101_snapshot.part-aaaaaa.age"},
+		{"manifests/tank/data/level1/20240108/task_manifest.yaml", "attachment; filename=tank_data_level1_20240108_task_manifest.yaml"},
+		{"manifests/tank/data/last_backup_manifest.yaml", "attachment; filename=tank_data_last_backup_manifest.yaml"},
+		{"task_manifest.yaml", "attachment; filename=task_manifest.yaml"},
+		{"data/tank/my data/level0/20240101/snapshot.part-aaaaaa.age", `attachment; filename="tank_my data_level0_20240101_snapshot.part-aaaaaa.age"`},
+		{"data/tank/café/level0/20240101/snapshot.part-aaaaaa.age", "attachment; filename*=utf-8''tank_caf%C3%A9_level0_20240101_snapshot.part-aaaaaa.age"},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.remotePath, func(t *testing.T) {
+			assert.Equal(t, tt.want, contentDisposition(tt.remotePath))
+		})
+	}
+}
+
+func TestBatches(t *testing.T) {
+	items := make([]string, 2500)
+	for i := range items {
+		items[i] = fmt.Sprint(i)
+	}
+
+	result := batches(items, maxDeleteBatch)
+	assert.Len(t, result, 3)
+	assert.Len(t, result[0], 1000)
+	assert.Len(t, result[1], 1000)
+	assert.Len(t, result[2], 500)
+	assert.Equal(t, "2499", result[2][499])
+
+	assert.Len(t, batches(items[:1000], maxDeleteBatch), 1)
+	assert.Empty(t, batches(nil, maxDeleteBatch))
+}
+
+func TestPresignDownload(t *testing.T) {
+	t.Setenv("AWS_ACCESS_KEY_ID", "test-key")
+	t.Setenv("AWS_SECRET_ACCESS_KEY", "test-secret")
+	t.Setenv("AWS_CONFIG_FILE", "/nonexistent")
+	t.Setenv("AWS_SHARED_CREDENTIALS_FILE", "/nonexistent")
+
+	backend, err := NewS3(context.Background(), "bucket", "us-east-1", "zrb", "http://localhost:9000", types.StorageClassStandard, 0)
+	require.NoError(t, err)
+
+	url, err := backend.PresignDownload(context.Background(), "data/tank/data/level0/20240101/snapshot.part-aaaaaa.age", time.Hour)
+	require.NoError(t, err)
+	assert.True(t, strings.HasPrefix(url, "http://localhost:9000/bucket/zrb/data/tank/data/level0/20240101/snapshot.part-aaaaaa.age?"), url)
+	assert.Contains(t, url, "X-Amz-Expires=3600")
+	assert.Contains(t, url, "X-Amz-Signature=")
+
+	_, err = backend.PresignDownload(context.Background(), "data/x", 8*24*time.Hour)
+	assert.ErrorContains(t, err, "presigned URL expiry must be between")
+}
@@ -0,0 +1,81 @@
+package restore
+
+import (
+	"context"
+	"fmt"
+	"os"
+	"path/filepath"
+	"time"
+	"zrb/internal/config"
+	"zrb/internal/manifest"
+	"zrb/internal/remote"
+)
+
+// Presign prints time-limited download URLs for the manifest and parts of the latest backup of a level,
+// so it can be fetched without AWS credentials. The parts stay encrypted to the configured age key.
+func Presign(ctx context.Context, configPath, taskName string, level int16, expires time.Duration) error {
+	cfg, err := config.Load(configPath)
+	if err != nil {
+		return fmt.Errorf("failed to load config: %w", err)
+	}
+
+	task, err := cfg.FindTask(taskName)
+	if err != nil {
+		return err
+	}
+
+	if !cfg.S3.Enabled {
+		return fmt.Errorf("S3 is not enabled in config")
+	}
+	if err := checkDataAccessible(cfg, level); err != nil {
+		return err
+	}
+
+	backend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region, cfg.S3.Prefix, cfg.S3.Endpoint,
+		cfg.S3.StorageClass.Manifest, cfg.S3RetryAttempts())
+	if err != nil {
+		return fmt.Errorf("failed to initialize S3 backend: %w", err)
+	}
+
+	f, err := os.CreateTemp("", "presign_last_manifest_*.yaml")
+	if err != nil {
+		return fmt.Errorf("failed to create temp file: %w", err)
+	}
+	f.Close()
+	lastManifestPath := f.Name()
+	defer os.Remove(lastManifestPath)
+
+	remoteLastPath := filepath.Join("manifests", task.Pool, task.Dataset, "last_backup_manifest.yaml")
+	if err := backend.Download(ctx, remoteLastPath, lastManifestPath); err != nil {
+		return fmt.Errorf("failed to download last backup manifest: %w", err)
+	}
+
+	lastBackup, err := manifest.ReadLast(lastManifestPath)
+	if err != nil {
+		return fmt.Errorf("failed to read last backup manifest: %w", err)
+	}
+	if int(level) >= len(lastBackup.BackupLevels) || lastBackup.BackupLevels[level] == nil {
+		return fmt.Errorf("backup level %d not found", level)
+	}
+
+	s3Path := lastBackup.BackupLevels[level].S3Path
+	m, err := downloadManifest(ctx, backend, s3Path)
+	if err != nil {
+		return fmt.Errorf("failed to download task manifest: %w", err)
+	}
+
+	remotePaths := []string{filepath.Join("manifests", s3Path, "task_manifest.yaml")}
+	for _, part := range m.Parts {
+		remotePaths = append(remotePaths, filepath.Join("data", m.TargetS3Path, fmt.Sprintf("snapshot.part-%s.age", part.Index)))
+	}
+
+	fmt.Printf("Download URLs for %s (valid until %s):\n", m.TargetSnapshot, time.Now().Add(expires).Format("2006-01-02 15:04:05"))
+	for _, remotePath := range remotePaths {
+		url, err := backend.PresignDownload(ctx, remotePath, expires)
+		if err != nil {
+			return err
+		}
+		fmt.Printf("%s\n  %s\n", filepath.Base(remotePath), url)
+	}
+	return nil
+}
@@ -8,6 +8,7 @@ import (
 	"os"
 	"os/exec"
 	"path/filepath"
+	"strconv"
 	"strings"
 	"time"
 	"zrb/internal/config"
@@ -19,8 +20,8 @@ import (
 	"filippo.io/age"
 )
 
-func Run(ctx context.Context, configPath, taskName string, level int16, target, privateKeyPath, source string, dryRun, force bool) error {
-	slog.Info("Restore started", "task", taskName, "level", level, "target", target, "source", source, "dryRun", dryRun)
+func Run(ctx context.Context, configPath, taskName string, level int16, target, privateKeyPath, source string, dryRun, force, chain bool) error {
+	slog.Info("Restore started", "task", taskName, "level", level, "target", target, "source", source, "dryRun", dryRun, "chain", chain)
 
 	cfg, err := config.Load(configPath)
 	if err != nil {
@@ -32,49 +33,34 @@ func Run(ctx context.Context, configPath, taskName string, level int16, target,
 		return err
 	}
 
-	targetParts := strings.Split(target, "/")
-	if len(targetParts) < 2 {
-		return fmt.Errorf("target must be in format pool/dataset, got: %s", target)
+	if err := config.ValidateDatasetName(target); err != nil {
+		return fmt.Errorf("target must be in format pool/dataset: %w", err)
 	}
+	targetParts := strings.Split(target, "/")
 
 	// Pre-flight: verify the target pool exists before downloading anything
 	if err := zfs.CheckPoolExists(targetParts[0]); err != nil {
 		return fmt.Errorf("pre-flight check: %w", err)
 	}
 
-	privateKeyData, err := os.ReadFile(privateKeyPath)
+	identity, err := crypto.LoadIdentity(privateKeyPath)
 	if err != nil {
-		return fmt.Errorf("failed to read private key: %w", err)
-	}
-
-	identity, err := age.ParseX25519Identity(strings.TrimSpace(string(privateKeyData)))
-	if err != nil {
-		return fmt.Errorf("failed to parse private key: %w", err)
+		return err
 	}
 
 	slog.Info("Private key loaded successfully")
 
 	var m *manifest.Backup
 	var manifestPath string
+	var manifestBackend *remote.S3
 
 	if source == "s3" {
 		if !cfg.S3.Enabled {
 			return fmt.Errorf("S3 is not enabled in config")
 		}
 
-		var storageClass string
-		if level >= 0 && int(level) < len(cfg.S3.StorageClass.BackupData) {
-			storageClass = string(cfg.S3.StorageClass.BackupData[level])
-		} else {
-			return fmt.Errorf("invalid backup level %d for configured storage classes", level)
-		}
-
-		if err := remote.ValidateStorageClass(storageClass); err != nil {
-			return fmt.Errorf("cannot restore from S3: backup data storage class is %s (not immediately accessible)\n"+
-				"You need to:\n"+
-				"1. Initiate a restore request in AWS S3 console or via AWS CLI\n"+
-				"2. Wait for the restore to complete (12-48 hours for DEEP_ARCHIVE)\n"+
-				"3. Then retry this restore command", storageClass)
+		if err := checkDataAccessible(cfg, level); err != nil {
+			return err
 		}
 
 		manifestStorageClass := string(cfg.S3.StorageClass.Manifest)
@@ -84,12 +70,13 @@ func Run(ctx context.Context, configPath, taskName string, level int16, target,
 
 		maxRetryAttempts := cfg.S3RetryAttempts()
 
-		backend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region,
+		manifestBackend, err = remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region,
 			cfg.S3.Prefix, cfg.S3.Endpoint,
 			cfg.S3.StorageClass.Manifest, maxRetryAttempts)
 		if err != nil {
 			return fmt.Errorf("failed to initialize S3 backend: %w", err)
 		}
+		backend := manifestBackend
 
 		if err := backend.VerifyCredentials(ctx); err != nil {
 			return fmt.Errorf("AWS credentials verification failed: %w", err)
@@ -149,30 +136,88 @@ func Run(ctx context.Context, configPath, taskName string, level int16, target,
 
 	slog.Info("Manifest loaded", "snapshot", m.TargetSnapshot, "parts", len(m.Parts), "blake3", m.Blake3Hash)
 
+	backups := []*manifest.Backup{m}
+	if chain {
+		backups, err = resolveChain(m, func(s3Path string) (*manifest.Backup, error) {
+			if source == "s3" {
+				return downloadManifest(ctx, manifestBackend, s3Path)
+			}
+			return manifest.Read(filepath.Join(cfg.BaseDir, "task", s3Path, "task_manifest.yaml"))
+		})
+		if err != nil {
+			return fmt.Errorf("failed to resolve backup chain: %w", err)
+		}
+		if source == "s3" {
+			for _, b := range backups {
+				if err := checkDataAccessible(cfg, b.BackupLevel); err != nil {
+					return err
+				}
+			}
+		}
+
+		// A re-run after a failed chain restore continues at the first backup the target is missing
+		backups = skipRestored(backups, func(b *manifest.Backup) bool {
+			return verifyRestoredSnapshot(target, b.TargetSnapshot) == nil
+		})
+		if len(backups) == 0 {
+			slog.Info("Every backup of the chain is already restored", "target", target)
+			return nil
+		}
+	}
+
 	if dryRun {
 		fmt.Printf("\n=== DRY RUN MODE ===\n")
-		fmt.Printf("Would restore backup:\n")
-		fmt.Printf("  Task:            %s\n", taskName)
-		fmt.Printf("  Pool/Dataset:    %s/%s\n", m.Pool, m.Dataset)
-		fmt.Printf("  Target:          %s\n", target)
-		fmt.Printf("  Backup Level:    %d\n", m.BackupLevel)
-		fmt.Printf("  Snapshot:        %s\n", m.TargetSnapshot)
-		if m.ParentSnapshot != "" {
-			fmt.Printf("  Parent Snapshot: %s\n", m.ParentSnapshot)
-		}
-		fmt.Printf("  Parts:           %d\n", len(m.Parts))
-		fmt.Printf("  BLAKE3 Hash:     %s\n", m.Blake3Hash)
-		fmt.Printf("  Source:          %s\n", source)
+		for _, b := range backups {
+			fmt.Printf("Would restore backup:\n")
+			fmt.Printf("  Task:            %s\n", taskName)
+			fmt.Printf("  Pool/Dataset:    %s/%s\n", b.Pool, b.Dataset)
+			fmt.Printf("  Target:          %s\n", target)
+			fmt.Printf("  Backup Level:    %d\n", b.BackupLevel)
+			fmt.Printf("  Snapshot:        %s\n", b.TargetSnapshot)
+			if b.ParentSnapshot != "" {
+				fmt.Printf("  Parent Snapshot: %s\n", b.ParentSnapshot)
+			}
+			fmt.Printf("  Parts:           %d\n", len(b.Parts))
+			fmt.Printf("  BLAKE3 Hash:     %s\n", b.Blake3Hash)
+			fmt.Printf("  Source:          %s\n", source)
+		}
 		fmt.Printf("\nNo changes made.\n")
 		return nil
 	}
 
-	tempDir := filepath.Join(cfg.BaseDir, "tmp", fmt.Sprintf("restore_%s_%d_%d", taskName, level, time.Now().Unix()))
+	for _, b := range backups {
+		if err := restoreBackup(ctx, cfg, taskName, b, target, source, identity, force); err != nil {
+			return fmt.Errorf("level %d backup %s: %w", b.BackupLevel, b.TargetSnapshot, err)
+		}
+	}
+
+	if err := removeLeftoverTempDirs(filepath.Join(cfg.BaseDir, "tmp"), taskName); err != nil {
+		slog.Warn("Failed to remove parts kept by earlier failed restores", "error", err)
+	}
+
+	slog.Info("Restore completed successfully!")
+
+	return nil
+}
+
+// restoreBackup fetches, decrypts and verifies the parts of one backup and receives it into target
+// The temp directory is named after the backup and kept on failure, so a re-run only fetches the missing parts.
+func restoreBackup(ctx context.Context, cfg *config.Config, taskName string, m *manifest.Backup, target, source string, identity age.Identity, force bool) (err error) {
+	level := m.BackupLevel
+
+	tempDir := filepath.Join(cfg.BaseDir, "tmp", fmt.Sprintf("restore_%s_%d_%d", taskName, level, m.Datetime))
 	if err := os.MkdirAll(tempDir, 0o755); err != nil {
 		return fmt.Errorf("failed to create temp directory: %w", err)
 	}
 
 	defer func() {
+		if err != nil {
+			slog.Info("Keeping encrypted parts to resume the restore", "path", tempDir)
+			if err := removeDecrypted(tempDir); err != nil {
+				slog.Warn("Failed to remove decrypted files", "path", tempDir, "error", err)
+			}
+			return
+		}
 		slog.Info("Cleaning up temp directory", "path", tempDir)
 		if err := os.RemoveAll(tempDir); err != nil {
 			slog.Warn("Failed to remove temp directory", "error", err)
@@ -181,6 +226,15 @@ func Run(ctx context.Context, configPath, taskName string, level int16, target,
 
 	slog.Info("Created temp directory", "path", tempDir)
 
+	var backend *remote.S3
+	if source == "s3" {
+		backend, err = remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region,
+			cfg.S3.Prefix, cfg.S3.Endpoint, cfg.S3.StorageClass.BackupData[level], cfg.S3RetryAttempts())
+		if err != nil {
+			return fmt.Errorf("failed to initialize S3 backend: %w", err)
+		}
+	}
+
 	slog.Info("Processing parts", "count", len(m.Parts))
 	decryptedParts := make([]string, len(m.Parts))
 
@@ -188,16 +242,9 @@ func Run(ctx context.Context, configPath, taskName string, level int16, target,
 		encryptedFile := filepath.Join(tempDir, fmt.Sprintf("snapshot.part-%s.age", partInfo.Index))
 		decryptedFile := filepath.Join(tempDir, fmt.Sprintf("snapshot.part-%s", partInfo.Index))
 
-		if source == "s3" {
-			maxRetryAttempts := cfg.S3RetryAttempts()
-			storageClass := cfg.S3.StorageClass.BackupData[level]
-
-			backend, err := remote.NewS3(ctx, cfg.S3.Bucket, cfg.S3.Region,
-				cfg.S3.Prefix, cfg.S3.Endpoint, storageClass, maxRetryAttempts)
-			if err != nil {
-				return fmt.Errorf("failed to initialize S3 backend: %w", err)
-			}
-
+		if partFetched(encryptedFile, partInfo.Blake3Hash) {
+			slog.Info("Part already fetched, skipping download", "part", partInfo.Index)
+		} else if source == "s3" {
 			remotePath := filepath.Join("data", m.TargetS3Path, fmt.Sprintf("snapshot.part-%s.age", partInfo.Index))
 			slog.Info("Downloading part from S3", "part", partInfo.Index, "remote", remotePath)
 
@@ -255,8 +302,87 @@ func Run(ctx context.Context, configPath, taskName string, level int16, target,
 		return fmt.Errorf("restore verification failed: %w", err)
 	}
 
-	slog.Info("Restore completed successfully!")
+	return nil
+}
+
+// skipRestored drops the leading backups of a chain that restored reports as already received
+func skipRestored(backups []*manifest.Backup, restored func(*manifest.Backup) bool) []*manifest.Backup {
+	for len(backups) > 0 && restored(backups[0]) {
+		slog.Info("Backup already restored, skipping", "level", backups[0].BackupLevel, "snapshot", backups[0].TargetSnapshot)
+		backups = backups[1:]
+	}
+	return backups
+}
 
+// removeLeftoverTempDirs deletes the restore_<task>_<level>_<datetime> directories failed restores of
+// the task kept under tmpDir, which are of no use once a restore of the task has succeeded
+func removeLeftoverTempDirs(tmpDir, taskName string) error {
+	entries, err := os.ReadDir(tmpDir)
+	if err != nil {
+		if os.IsNotExist(err) {
+			return nil
+		}
+		return err
+	}
+
+	prefix := fmt.Sprintf("restore_%s_", taskName)
+	for _, e := range entries {
+		rest, found := strings.CutPrefix(e.Name(), prefix)
+		if !found || !e.IsDir() {
+			continue
+		}
+		// skip tasks whose name merely starts with this one, e.g. restore_<task>_daily_0_1704067200
+		levelStr, datetimeStr, ok := strings.Cut(rest, "_")
+		if !ok {
+			continue
+		}
+		if _, err := strconv.ParseInt(levelStr, 10, 16); err != nil {
+			continue
+		}
+		if _, err := strconv.ParseInt(datetimeStr, 10, 64); err != nil {
+			continue
+		}
+
+		slog.Info("Removing parts kept by an earlier failed restore", "path", filepath.Join(tmpDir, e.Name()))
+		if err := os.RemoveAll(filepath.Join(tmpDir, e.Name())); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// partFetched reports whether encryptedFile was fetched by an earlier attempt and matches its manifest hash
+func partFetched(encryptedFile, blake3Hash string) bool {
+	if _, err := os.Stat(encryptedFile); err != nil {
+		return false
+	}
+	actual, err := crypto.BLAKE3File(encryptedFile)
+	if err != nil {
+		slog.Warn("Failed to hash previously fetched part", "path", encryptedFile, "error", err)
+		return false
+	}
+	if actual != blake3Hash {
+		slog.Warn("Previously fetched part is corrupt, fetching again", "path", encryptedFile)
+		return false
+	}
+	return true
+}
+
+// removeDecrypted deletes everything in tempDir but the encrypted .age parts, so plaintext
+// never outlives a failed restore
+func removeDecrypted(tempDir string) error {
+	entries, err := os.ReadDir(tempDir)
+	if err != nil {
+		return err
+	}
+	for _, e := range entries {
+		if strings.HasSuffix(e.Name(), ".age") {
+			continue
+		}
+		if err := os.RemoveAll(filepath.Join(tempDir, e.Name())); err != nil {
+			return err
+		}
+	}
 	return nil
 }
 
@@ -344,3 +470,62 @@ func executeZfsReceive(snapshotFile, target string, force bool) error {
 
 	return nil
 }
+
+// resolveChain follows ParentS3Path from m down to its level 0 backup and returns the
+// backups in the order they must be received, failing if a link is missing or does not match
+func resolveChain(m *manifest.Backup, load func(s3Path string) (*manifest.Backup, error)) ([]*manifest.Backup, error) {
+	backups := []*manifest.Backup{m}
+	for current := m; current.BackupLevel > 0; {
+		if current.ParentS3Path == "" {
+			return nil, fmt.Errorf("level %d backup %s does not record its parent backup", current.BackupLevel, current.TargetSnapshot)
+		}
+
+		parent, err := load(current.ParentS3Path)
+		if err != nil {
+			return nil, fmt.Errorf("parent of level %d backup %s is missing: %w", current.BackupLevel, current.TargetSnapshot, err)
+		}
+		if parent.TargetSnapshot != current.ParentSnapshot {
+			return nil, fmt.Errorf("backup %s was taken from %s, but level %d backup is based on %s",
+				current.ParentS3Path, parent.TargetSnapshot, current.BackupLevel, current.ParentSnapshot)
+		}
+		if parent.BackupLevel >= current.BackupLevel {
+			return nil, fmt.Errorf("parent of level %d backup %s has level %d", current.BackupLevel, current.TargetSnapshot, parent.BackupLevel)
+		}
+
+		backups = append([]*manifest.Backup{parent}, backups...)
+		current = parent
+	}
+	return backups, nil
+}
+
+func downloadManifest(ctx context.Context, backend *remote.S3, s3Path string) (*manifest.Backup, error) {
+	f, err := os.CreateTemp("", "restore_manifest_*.yaml")
+	if err != nil {
+		return nil, fmt.Errorf("failed to create temp file: %w", err)
+	}
+	f.Close()
+	defer os.Remove(f.Name())
+
+	remoteManifestPath := filepath.Join("manifests", s3Path, "task_manifest.yaml")
+	slog.Info("Downloading task manifest from S3", "remote", remoteManifestPath)
+	if err := backend.Download(ctx, remoteManifestPath, f.Name()); err != nil {
+		return nil, err
+	}
+	return manifest.Read(f.Name())
+}
+
+func checkDataAccessible(cfg *config.Config, level int16) error {
+	if level < 0 || int(level) >= len(cfg.S3.StorageClass.BackupData) {
+		return fmt.Errorf("invalid backup level %d for configured storage classes", level)
+	}
+
+	storageClass := string(cfg.S3.StorageClass.BackupData[level])
+	if err := remote.ValidateStorageClass(storageClass); err != nil {
+		return fmt.Errorf("cannot restore from S3: backup data storage class is %s (not immediately accessible)\n"+
+			"You need to:\n"+
+			"1. Initiate a restore request in AWS S3 console or via AWS CLI\n"+
+			"2. Wait for the restore to complete (12-48 hours for DEEP_ARCHIVE)\n"+
+			"3. Then retry this restore command", storageClass)
+	}
+	return nil
+}
@@ -0,0 +1,195 @@
+package restore
+
+import (
+	"context"
+	"fmt"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+	"zrb/internal/config"
+	"zrb/internal/crypto"
+	"zrb/internal/manifest"
+
+	"filippo.io/age"
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestResolveChain(t *testing.T) {
+	full := &manifest.Backup{BackupLevel: 0, TargetSnapshot: "tank/data@zrb_level0_2024-01-01_00-00", TargetS3Path: "tank/data/level0/20240101"}
+	newerFull := &manifest.Backup{BackupLevel: 0, TargetSnapshot: "tank/data@zrb_level0_2024-02-01_00-00", TargetS3Path: "tank/data/level0/20240201"}
+	incr1 := &manifest.Backup{
+		BackupLevel: 1, TargetSnapshot: "tank/data@zrb_level1_2024-01-08_00-00", TargetS3Path: "tank/data/level1/20240108",
+		ParentSnapshot: full.TargetSnapshot, ParentS3Path: full.TargetS3Path,
+	}
+	incr2 := &manifest.Backup{
+		BackupLevel: 2, TargetSnapshot: "tank/data@zrb_level2_2024-01-09_00-00", TargetS3Path: "tank/data/level2/20240109",
+		ParentSnapshot: incr1.TargetSnapshot, ParentS3Path: incr1.TargetS3Path,
+	}
+
+	loader := func(backups ...*manifest.Backup) func(string) (*manifest.Backup, error) {
+		byPath := make(map[string]*manifest.Backup)
+		for _, b := range backups {
+			byPath[b.TargetS3Path] = b
+		}
+		return func(s3Path string) (*manifest.Backup, error) {
+			if b, ok := byPath[s3Path]; ok {
+				return b, nil
+			}
+			return nil, fmt.Errorf("manifest %s not found", s3Path)
+		}
+	}
+
+	t.Run("full and two incrementals", func(t *testing.T) {
+		chain, err := resolveChain(incr2, loader(full, incr1, newerFull))
+		require.NoError(t, err)
+		assert.Equal(t, []*manifest.Backup{full, incr1, incr2}, chain)
+	})
+
+	t.Run("full only", func(t *testing.T) {
+		chain, err := resolveChain(full, loader())
+		require.NoError(t, err)
+		assert.Equal(t, []*manifest.Backup{full}, chain)
+	})
+
+	t.Run("missing link", func(t *testing.T) {
+		_, err := resolveChain(incr2, loader(full))
+		assert.ErrorContains(t, err, "parent of level 2 backup tank/data@zrb_level2_2024-01-09_00-00 is missing")
+	})
+
+	t.Run("parent snapshot mismatch", func(t *testing.T) {
+		replaced := *newerFull
+		replaced.TargetS3Path = full.TargetS3Path
+		_, err := resolveChain(incr1, loader(&replaced))
+		assert.ErrorContains(t, err, "but level 1 backup is based on tank/data@zrb_level0_2024-01-01_00-00")
+	})
+
+	t.Run("parent not recorded", func(t *testing.T) {
+		orphan := *incr1
+		orphan.ParentS3Path = ""
+		_, err := resolveChain(&orphan, loader(full))
+		assert.ErrorContains(t, err, "does not record its parent backup")
+	})
+}
+
+func TestPartFetched(t *testing.T) {
+	dir := t.TempDir()
+	part := filepath.Join(dir, "snapshot.part-aaaaaa.age")
+	require.NoError(t, os.WriteFile(part, []byte("encrypted part"), 0o644))
+	hash, err := crypto.BLAKE3File(part)
+	require.NoError(t, err)
+
+	assert.True(t, partFetched(part, hash))
+	assert.False(t, partFetched(part, "0000"), "corrupt part must be fetched again")
+	assert.False(t, partFetched(filepath.Join(dir, "snapshot.part-aaaaab.age"), hash))
+}
+
+func TestRemoveDecrypted(t *testing.T) {
+	dir := t.TempDir()
+	for _, name := range []string{"snapshot.part-aaaaaa.age", "snapshot.part-aaaaaa", "snapshot.part-aaaaab.age", "snapshot.merged"} {
+		require.NoError(t, os.WriteFile(filepath.Join(dir, name), []byte(name), 0o644))
+	}
+
+	require.NoError(t, removeDecrypted(dir))
+
+	entries, err := os.ReadDir(dir)
+	require.NoError(t, err)
+	var names []string
+	for _, e := range entries {
+		names = append(names, e.Name())
+	}
+	assert.Equal(t, []string{"snapshot.part-aaaaaa.age", "snapshot.part-aaaaab.age"}, names)
+}
+
+func TestRestoreBackupResume(t *testing.T) {
+	baseDir := t.TempDir()
+	cfg := &config.Config{BaseDir: baseDir}
+	identity, err := age.GenerateX25519Identity()
+	require.NoError(t, err)
+
+	m := &manifest.Backup{Datetime: 1704067200, Pool: "tank", Dataset: "data", TargetSnapshot: "tank/data@zrb_level0_2024-01-01_00-00"}
+	sourceDir := filepath.Join(baseDir, "task", "tank", "data", "level0", time.Unix(m.Datetime, 0).Format("20060102"))
+	require.NoError(t, os.MkdirAll(sourceDir, 0o755))
+
+	var stream []byte
+	for _, index := range []string{"aaaaaa", "aaaaab"} {
+		plain := filepath.Join(t.TempDir(), "snapshot.part-"+index)
+		content := []byte("stream part " + index)
+		stream = append(stream, content...)
+		require.NoError(t, os.WriteFile(plain, content, 0o644))
+		hash, err := crypto.EncryptAndHash(plain, filepath.Join(sourceDir, "snapshot.part-"+index+".age"), identity.Recipient())
+		require.NoError(t, err)
+		m.Parts = append(m.Parts, manifest.PartInfo{Index: index, Blake3Hash: hash})
+	}
+	streamFile := filepath.Join(t.TempDir(), "stream")
+	require.NoError(t, os.WriteFile(streamFile, stream, 0o644))
+	m.Blake3Hash, err = crypto.BLAKE3File(streamFile)
+	require.NoError(t, err)
+
+	goodPart, err := os.ReadFile(filepath.Join(sourceDir, "snapshot.part-aaaaab.age"))
+	require.NoError(t, err)
+	require.NoError(t, os.WriteFile(filepath.Join(sourceDir, "snapshot.part-aaaaab.age"), []byte("corrupt"), 0o644))
+
+	// the nonexistent pool makes zfs receive fail even where zfs is installed
+	const target = "zrb_test_missing_pool/restore"
+	tempDir := filepath.Join(baseDir, "tmp", "restore_t1_0_1704067200")
+
+	err = restoreBackup(context.Background(), cfg, "t1", m, target, "local", identity, false)
+	assert.ErrorContains(t, err, "failed to decrypt/verify part aaaaab")
+
+	entries, err := os.ReadDir(tempDir)
+	require.NoError(t, err)
+	var kept []string
+	for _, e := range entries {
+		kept = append(kept, e.Name())
+	}
+	assert.Equal(t, []string{"snapshot.part-aaaaaa.age", "snapshot.part-aaaaab.age"}, kept, "only encrypted parts are kept")
+
+	// the re-run must reuse the fetched first part and fetch the corrupt second part again
+	require.NoError(t, os.Remove(filepath.Join(sourceDir, "snapshot.part-aaaaaa.age")))
+	require.NoError(t, os.WriteFile(filepath.Join(sourceDir, "snapshot.part-aaaaab.age"), goodPart, 0o644))
+
+	err = restoreBackup(context.Background(), cfg, "t1", m, target, "local", identity, false)
+	assert.ErrorContains(t, err, "ZFS receive failed")
+	assert.True(t, partFetched(filepath.Join(tempDir, "snapshot.part-aaaaab.age"), m.Parts[1].Blake3Hash))
+}
+
+func TestSkipRestored(t *testing.T) {
+	full := &manifest.Backup{BackupLevel: 0, TargetSnapshot: "tank/data@zrb_level0_2024-01-01_00-00"}
+	incr1 := &manifest.Backup{BackupLevel: 1, TargetSnapshot: "tank/data@zrb_level1_2024-01-08_00-00"}
+	incr2 := &manifest.Backup{BackupLevel: 2, TargetSnapshot: "tank/data@zrb_level2_2024-01-09_00-00"}
+	chain := []*manifest.Backup{full, incr1, incr2}
+
+	restored := func(backups ...*manifest.Backup) func(*manifest.Backup) bool {
+		return func(b *manifest.Backup) bool {
+			for _, r := range backups {
+				if r == b {
+					return true
+				}
+			}
+			return false
+		}
+	}
+
+	assert.Equal(t, chain, skipRestored(chain, restored()))
+	assert.Equal(t, []*manifest.Backup{incr2}, skipRestored(chain, restored(full, incr1)))
+	assert.Empty(t, skipRestored(chain, restored(full, incr1, incr2)))
+}
+
+func TestRemoveLeftoverTempDirs(t *testing.T) {
+	tmpDir := t.TempDir()
+	for _, name := range []string{"restore_t1_0_1704067200", "restore_t1_1_1704672000", "restore_t1_daily_0_1704067200", "restore_t2_0_1704067200"} {
+		require.NoError(t, os.MkdirAll(filepath.Join(tmpDir, name), 0o755))
+		require.NoError(t, os.WriteFile(filepath.Join(tmpDir, name, "snapshot.part-aaaaaa.age"), []byte("x"), 0o644))
+	}
+
+	require.NoError(t, removeLeftoverTempDirs(tmpDir, "t1"))
+
+	assert.NoDirExists(t, filepath.Join(tmpDir, "restore_t1_0_1704067200"))
+	assert.NoDirExists(t, filepath.Join(tmpDir, "restore_t1_1_1704672000"))
+	assert.DirExists(t, filepath.Join(tmpDir, "restore_t1_daily_0_1704067200"))
+	assert.DirExists(t, filepath.Join(tmpDir, "restore_t2_0_1704067200"))
+
+	assert.NoError(t, removeLeftoverTempDirs(filepath.Join(tmpDir, "missing"), "t1"))
+}
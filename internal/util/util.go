@@ -5,6 +5,7 @@ import (
 	"log/slog"
 	"os"
 	"path/filepath"
+	"syscall"
 	"time"
 	"zrb/internal/logging"
 )
@@ -28,6 +29,15 @@ func LogDir(baseDir, pool, dataset string) string {
 	return filepath.Join(baseDir, "logs", pool, dataset)
 }
 
+// AvailableSpace returns the bytes available to unprivileged users on the filesystem containing path
+func AvailableSpace(path string) (int64, error) {
+	var st syscall.Statfs_t
+	if err := syscall.Statfs(path, &st); err != nil {
+		return 0, fmt.Errorf("failed to stat filesystem of %s: %w", path, err)
+	}
+	return int64(st.Bavail) * int64(st.Bsize), nil
+}
+
 func SetupDirectories(dirs ...string) error {
 	for _, dir := range dirs {
 		if err := os.MkdirAll(dir, 0o755); err != nil {
@@ -98,3 +98,12 @@ func TestLogDir(t *testing.T) {
 		})
 	}
 }
+
+func TestAvailableSpace(t *testing.T) {
+	available, err := AvailableSpace(t.TempDir())
+	assert.NoError(t, err)
+	assert.Positive(t, available)
+
+	_, err = AvailableSpace("/nonexistent/zrb/path")
+	assert.Error(t, err)
+}
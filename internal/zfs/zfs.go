@@ -9,6 +9,7 @@ import (
 	"os/exec"
 	"path/filepath"
 	"sort"
+	"strconv"
 	"strings"
 	"sync"
 	"time"
@@ -16,8 +17,56 @@ import (
 	"github.com/zeebo/blake3"
 )
 
-// SendAndSplit executes zfs send and splits the output into parts while computing BLAKE3 hash
-func SendAndSplit(ctx context.Context, targetSnapshot, parentSnapshot, exportDir string) (string, error) {
+// SendOptions selects optional zfs send flags
+type SendOptions struct {
+	LargeBlocks *bool `yaml:"large_blocks"` // -L, on unless set to false
+	Embedded    bool  `yaml:"embedded"`     // -e
+	Compressed  bool  `yaml:"compressed"`   // -c
+	Raw         bool  `yaml:"raw"`          // -w
+	Properties  bool  `yaml:"properties"`   // -p
+}
+
+func sendArgs(opts SendOptions, targetSnapshot, parentSnapshot string, dryRun bool) []string {
+	args := []string{"send"}
+	if dryRun {
+		args = append(args, "-n", "-P")
+	}
+	if opts.LargeBlocks == nil || *opts.LargeBlocks {
+		args = append(args, "-L")
+	}
+	if opts.Embedded {
+		args = append(args, "-e")
+	}
+	if opts.Compressed {
+		args = append(args, "-c")
+	}
+	if opts.Raw {
+		args = append(args, "-w")
+	}
+	if opts.Properties {
+		args = append(args, "-p")
+	}
+	if parentSnapshot != "" {
+		args = append(args, "-i", parentSnapshot)
+	}
+	return append(args, targetSnapshot)
+}
+
+const partSuffixLength = 6
+
+// PartSuffix returns the suffix split gives the n-th part (0-based): aaaaaa, aaaaab, ...
+func PartSuffix(n int) string {
+	suffix := make([]byte, partSuffixLength)
+	for i := partSuffixLength - 1; i >= 0; i-- {
+		suffix[i] = byte('a' + n%26)
+		n /= 26
+	}
+	return string(suffix)
+}
+
+// SendAndSplit executes zfs send and splits the output into parts while computing BLAKE3 hash,
+// returning the hash and the size of the raw stream in bytes
+func SendAndSplit(ctx context.Context, opts SendOptions, targetSnapshot, parentSnapshot, exportDir string, splitSize int64) (string, int64, error) {
 	ctx, cancel := context.WithCancel(ctx)
 	defer cancel()
 
@@ -36,18 +85,16 @@ func SendAndSplit(ctx context.Context, targetSnapshot, parentSnapshot, exportDir
 		}
 	}()
 
-	args := []string{"send", "-L"}
+	args := sendArgs(opts, targetSnapshot, parentSnapshot, false)
 	if parentSnapshot != "" {
-		args = append(args, "-i", parentSnapshot)
-		slog.Info("Running incremental send", "parentSnapshot", parentSnapshot, "snapshot", targetSnapshot)
+		slog.Info("Running incremental send", "parentSnapshot", parentSnapshot, "snapshot", targetSnapshot, "args", args)
 	} else {
-		slog.Info("Running full send", "snapshot", targetSnapshot)
+		slog.Info("Running full send", "snapshot", targetSnapshot, "args", args)
 	}
-	args = append(args, targetSnapshot)
 	zfsCmd := exec.CommandContext(ctx, "zfs", args...)
 	zfsCmd.Stderr = os.Stderr
 
-	splitCmd := exec.CommandContext(ctx, "split", "-b", "3G", "-a", "6", "--additional-suffix=.tmp", "-", outputPatternTmp)
+	splitCmd := exec.CommandContext(ctx, "split", "-b", strconv.FormatInt(splitSize, 10), "-a", strconv.Itoa(partSuffixLength), "--additional-suffix=.tmp", "-", outputPatternTmp)
 	splitCmd.Stderr = os.Stderr
 
 	holdTag := fmt.Sprintf("zrb:%d", time.Now().Unix())
@@ -55,7 +102,7 @@ func SendAndSplit(ctx context.Context, targetSnapshot, parentSnapshot, exportDir
 	if err := exec.CommandContext(holdCtx, "zfs", "hold", holdTag, targetSnapshot).Run(); err != nil {
 		cancelHold()
 		slog.Error("Failed to hold snapshot", "snapshot", targetSnapshot, "error", err)
-		return "", fmt.Errorf("failed to hold snapshot: %w", err)
+		return "", 0, fmt.Errorf("failed to hold snapshot: %w", err)
 	}
 	cancelHold()
 	defer func() {
@@ -68,18 +115,19 @@ func SendAndSplit(ctx context.Context, targetSnapshot, parentSnapshot, exportDir
 
 	pr, pw, err := os.Pipe()
 	if err != nil {
-		return "", fmt.Errorf("failed to create pipe: %w", err)
+		return "", 0, fmt.Errorf("failed to create pipe: %w", err)
 	}
 	zfsCmd.Stdout = pw
 
 	hasher := blake3.New()
-	splitCmd.Stdin = io.TeeReader(pr, hasher)
+	counter := &countingWriter{}
+	splitCmd.Stdin = io.TeeReader(pr, io.MultiWriter(hasher, counter))
 
 	if err := splitCmd.Start(); err != nil {
 		pw.Close()
 		pr.Close()
 		slog.Error("Failed to start split command", "error", err)
-		return "", fmt.Errorf("failed to start split: %w", err)
+		return "", 0, fmt.Errorf("failed to start split: %w", err)
 	}
 
 	if err := zfsCmd.Start(); err != nil {
@@ -88,7 +136,7 @@ func SendAndSplit(ctx context.Context, targetSnapshot, parentSnapshot, exportDir
 		_ = splitCmd.Process.Kill()
 		_ = splitCmd.Wait()
 		slog.Error("Failed to start zfs command", "error", err)
-		return "", fmt.Errorf("failed to start zfs: %w", err)
+		return "", 0, fmt.Errorf("failed to start zfs: %w", err)
 	}
 
 	// Close our copy of the write end so split gets EOF when zfs exits.
@@ -132,31 +180,64 @@ func SendAndSplit(ctx context.Context, targetSnapshot, parentSnapshot, exportDir
 
 	if len(errs) > 0 {
 		slog.Error("Pipeline failed", "errors", errs)
-		return "", fmt.Errorf("pipeline failed: %v", errs)
+		return "", 0, fmt.Errorf("pipeline failed: %v", errs)
 	}
 
 	matches, err := filepath.Glob(outputPatternTmp + "*.tmp")
 	if err != nil {
 		slog.Error("Failed to glob tmp files", "error", err)
-		return "", fmt.Errorf("failed to glob tmp files: %w", err)
+		return "", 0, fmt.Errorf("failed to glob tmp files: %w", err)
 	}
 	for _, tmpFile := range matches {
 		finalFile := strings.TrimSuffix(tmpFile, ".tmp")
 		if err := os.Rename(tmpFile, finalFile); err != nil {
 			slog.Error("Failed to rename tmp file", "tmpFile", tmpFile, "finalFile", finalFile, "error", err)
-			return "", fmt.Errorf("failed to rename tmp file: %w", err)
+			return "", 0, fmt.Errorf("failed to rename tmp file: %w", err)
 		}
 		slog.Debug("Renamed tmp file", "tmpFile", tmpFile, "finalFile", finalFile)
 	}
 
 	success = true
 	blake3Hash := fmt.Sprintf("%x", hasher.Sum(nil))
-	slog.Info("ZFS send and split completed successfully", "outputPattern", outputPattern, "blake3", blake3Hash)
+	slog.Info("ZFS send and split completed successfully", "outputPattern", outputPattern, "blake3", blake3Hash, "streamBytes", counter.n)
 
-	return blake3Hash, nil
+	return blake3Hash, counter.n, nil
 }
 
-func ListSnapshots(pool, dataset, prefix string) ([]string, error) {
+// countingWriter counts the bytes written to it
+type countingWriter struct {
+	n int64
+}
+
+func (w *countingWriter) Write(p []byte) (int, error) {
+	w.n += int64(len(p))
+	return len(p), nil
+}
+
+// EstimateSendSize returns the estimated stream size in bytes from a zfs send dry run
+func EstimateSendSize(ctx context.Context, opts SendOptions, targetSnapshot, parentSnapshot string) (int64, error) {
+	output, err := exec.CommandContext(ctx, "zfs", sendArgs(opts, targetSnapshot, parentSnapshot, true)...).Output()
+	if err != nil {
+		return 0, fmt.Errorf("zfs send dry run failed: %w", err)
+	}
+	return parseSendSize(string(output))
+}
+
+func parseSendSize(output string) (int64, error) {
+	for _, line := range strings.Split(output, "\n") {
+		fields := strings.Fields(line)
+		if len(fields) == 2 && fields[0] == "size" {
+			size, err := strconv.ParseInt(fields[1], 10, 64)
+			if err != nil {
+				return 0, fmt.Errorf("invalid size in zfs send output: %q", line)
+			}
+			return size, nil
+		}
+	}
+	return 0, fmt.Errorf("size not found in zfs send output")
+}
+
+func ListSnapshots(pool, dataset string, level int16) ([]string, error) {
 	cmd := exec.Command(
 		"zfs",
 		"list",
@@ -175,13 +256,8 @@ func ListSnapshots(pool, dataset, prefix string) ([]string, error) {
 	var snapshots []string
 	lines := strings.Split(strings.TrimSpace(string(output)), "\n")
 	for _, line := range lines {
-		parts := strings.SplitN(line, "@", 2)
-		if len(parts) != 2 {
-			continue
-		}
-
-		snapName := parts[1]
-		if prefix != "" && !strings.HasPrefix(snapName, prefix) {
+		info, ok := ParseSnapshotName(line)
+		if !ok || info.Level != level {
 			continue
 		}
 
@@ -195,6 +271,90 @@ func ListSnapshots(pool, dataset, prefix string) ([]string, error) {
 	return snapshots, nil
 }
 
+type SnapshotInfo struct {
+	Name    string
+	Created time.Time
+	Used    int64 // bytes
+}
+
+// ListSnapshotsDetailed returns every snapshot of the dataset with its creation time and used space
+func ListSnapshotsDetailed(pool, dataset string) ([]SnapshotInfo, error) {
+	cmd := exec.Command("zfs", "list", "-H", "-p", "-o", "name,creation,used", "-t", "snapshot", "-d", "1", fmt.Sprintf("%s/%s", pool, dataset))
+	output, err := cmd.Output()
+	if err != nil {
+		return nil, err
+	}
+	return parseSnapshotList(string(output))
+}
+
+func parseSnapshotList(output string) ([]SnapshotInfo, error) {
+	var snapshots []SnapshotInfo
+	for _, line := range strings.Split(strings.TrimSpace(output), "\n") {
+		if line == "" {
+			continue
+		}
+
+		fields := strings.Split(line, "\t")
+		if len(fields) != 3 {
+			return nil, fmt.Errorf("unexpected zfs list output: %q", line)
+		}
+
+		created, err := strconv.ParseInt(fields[1], 10, 64)
+		if err != nil {
+			return nil, fmt.Errorf("invalid creation time for %s: %w", fields[0], err)
+		}
+		used, err := strconv.ParseInt(fields[2], 10, 64)
+		if err != nil {
+			return nil, fmt.Errorf("invalid used size for %s: %w", fields[0], err)
+		}
+
+		snapshots = append(snapshots, SnapshotInfo{Name: fields[0], Created: time.Unix(created, 0), Used: used})
+	}
+	return snapshots, nil
+}
+
+const (
+	snapshotPrefix     = "zrb_level"
+	snapshotTimeLayout = "2006-01-02_15-04"
+)
+
+type SnapshotNameInfo struct {
+	Level   int16
+	Created time.Time // zero if the suffix is not a zrb timestamp
+}
+
+// SnapshotName returns the snapshot name zrb uses for a backup level, e.g. zrb_level0_2024-01-01_00-00
+func SnapshotName(level int16, created time.Time) string {
+	return fmt.Sprintf("%s%d_%s", snapshotPrefix, level, created.Format(snapshotTimeLayout))
+}
+
+// ParseSnapshotName parses a snapshot name, with or without the dataset part.
+// Snapshots that do not start with zrb_level<N> are external and reported as not ok.
+func ParseSnapshotName(name string) (SnapshotNameInfo, bool) {
+	if _, snap, found := strings.Cut(name, "@"); found {
+		name = snap
+	}
+
+	rest, found := strings.CutPrefix(name, snapshotPrefix)
+	if !found {
+		return SnapshotNameInfo{}, false
+	}
+
+	digits := len(rest) - len(strings.TrimLeft(rest, "0123456789"))
+	level, err := strconv.ParseInt(rest[:digits], 10, 16)
+	if err != nil {
+		return SnapshotNameInfo{}, false
+	}
+
+	info := SnapshotNameInfo{Level: int16(level)}
+	if suffix, found := strings.CutPrefix(rest[digits:], "_"); found {
+		if created, err := time.ParseInLocation(snapshotTimeLayout, suffix, time.Local); err == nil {
+			info.Created = created
+		}
+	}
+	return info, true
+}
+
 func CheckDatasetExists(pool, dataset string) error {
 	cmd := exec.Command("zfs", "list", "-H", "-o", "name", fmt.Sprintf("%s/%s", pool, dataset))
 	if err := cmd.Run(); err != nil {
@@ -203,6 +363,22 @@ func CheckDatasetExists(pool, dataset string) error {
 	return nil
 }
 
+func CheckSnapshotExists(snapshot string) error {
+	cmd := exec.Command("zfs", "list", "-H", "-o", "name", "-t", "snapshot", snapshot)
+	if err := cmd.Run(); err != nil {
+		return fmt.Errorf("ZFS snapshot %s not found or not accessible", snapshot)
+	}
+	return nil
+}
+
+func CheckBookmarkExists(bookmark string) error {
+	cmd := exec.Command("zfs", "list", "-H", "-o", "name", "-t", "bookmark", bookmark)
+	if err := cmd.Run(); err != nil {
+		return fmt.Errorf("ZFS bookmark %s not found or not accessible", bookmark)
+	}
+	return nil
+}
+
 func CheckPoolExists(pool string) error {
 	cmd := exec.Command("zfs", "list", "-H", "-o", "name", pool)
 	if err := cmd.Run(); err != nil {
@@ -223,10 +399,28 @@ func Release(tag, snapshot string) error {
 	return exec.CommandContext(ctx, "zfs", "release", tag, snapshot).Run()
 }
 
-func CreateSnapshot(pool, dataset, prefix string) error {
-	date := time.Now().Format("2006-01-02_15-04")
-	fullSnapshotName := fmt.Sprintf("%s/%s@%s_%s", pool, dataset, prefix, date)
+// BookmarkName returns the name of the bookmark kept for snapshot, e.g. tank/data#zrb_level0_2024-01-01_00-00
+func BookmarkName(snapshot string) string {
+	return strings.Replace(snapshot, "@", "#", 1)
+}
+
+// CreateBookmark bookmarks snapshot so it can still be the base of an incremental send after being destroyed
+func CreateBookmark(snapshot string) error {
+	ctx, cancel := context.WithTimeout(context.Background(), 10*time.Second)
+	defer cancel()
+	return exec.CommandContext(ctx, "zfs", "bookmark", snapshot, BookmarkName(snapshot)).Run()
+}
+
+func CreateSnapshot(pool, dataset string, level int16) error {
+	return createSnapshot(fmt.Sprintf("%s/%s@%s", pool, dataset, SnapshotName(level, time.Now())))
+}
+
+// CreateSnapshotWithPrefix creates <prefix>_<date>, the naming of the deprecated snapshot --prefix flag
+func CreateSnapshotWithPrefix(pool, dataset, prefix string) error {
+	return createSnapshot(fmt.Sprintf("%s/%s@%s_%s", pool, dataset, prefix, time.Now().Format(snapshotTimeLayout)))
+}
 
+func createSnapshot(fullSnapshotName string) error {
 	cmd := exec.Command("zfs", "snapshot", fullSnapshotName)
 	cmd.Stdout = os.Stdout
 	cmd.Stderr = os.Stderr
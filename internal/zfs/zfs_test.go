@@ -0,0 +1,166 @@
+package zfs
+
+import (
+	"io"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/assert"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseSendSize(t *testing.T) {
+	t.Run("full send", func(t *testing.T) {
+		size, err := parseSendSize("full\ttank/data@zrb_level0_2024-01-01_00-00\t10737418240\nsize\t10737418240\n")
+		require.NoError(t, err)
+		assert.Equal(t, int64(10737418240), size)
+	})
+
+	t.Run("incremental send", func(t *testing.T) {
+		size, err := parseSendSize("incremental\tzrb_level0_2024-01-01_00-00\ttank/data@zrb_level1_2024-01-02_00-00\t4096\nsize\t4096\n")
+		require.NoError(t, err)
+		assert.Equal(t, int64(4096), size)
+	})
+
+	t.Run("missing size line", func(t *testing.T) {
+		_, err := parseSendSize("full\ttank/data@snap\t4096\n")
+		assert.ErrorContains(t, err, "size not found")
+	})
+
+	t.Run("invalid size", func(t *testing.T) {
+		_, err := parseSendSize("size\tabc\n")
+		assert.ErrorContains(t, err, "invalid size")
+	})
+}
+
+func TestSnapshotNameRoundTrip(t *testing.T) {
+	created := time.Date(2024, 3, 15, 2, 30, 0, 0, time.Local)
+	name := SnapshotName(2, created)
+	assert.Equal(t, "zrb_level2_2024-03-15_02-30", name)
+
+	info, ok := ParseSnapshotName("tank/data@" + name)
+	require.True(t, ok)
+	assert.Equal(t, int16(2), info.Level)
+	assert.True(t, created.Equal(info.Created))
+}
+
+func TestParseSnapshotName(t *testing.T) {
+	tests := []struct {
+		name      string
+		input     string
+		wantOk    bool
+		wantLevel int16
+		wantTime  bool
+	}{
+		{name: "zrb snapshot", input: "zrb_level0_2024-01-01_00-00", wantOk: true, wantLevel: 0, wantTime: true},
+		{name: "double digit level", input: "tank/data@zrb_level10_2024-01-01_00-00", wantOk: true, wantLevel: 10, wantTime: true},
+		{name: "custom suffix", input: "zrb_level1_daily", wantOk: true, wantLevel: 1, wantTime: false},
+		{name: "no suffix", input: "zrb_level3", wantOk: true, wantLevel: 3, wantTime: false},
+		{name: "other separator", input: "zrb_level0-2024-01-01", wantOk: true, wantLevel: 0, wantTime: false},
+		{name: "external snapshot", input: "tank/data@auto-2024-01-01_00-00", wantOk: false},
+		{name: "missing level", input: "zrb_level_2024-01-01_00-00", wantOk: false},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			info, ok := ParseSnapshotName(tt.input)
+			assert.Equal(t, tt.wantOk, ok)
+			if tt.wantOk {
+				assert.Equal(t, tt.wantLevel, info.Level)
+				assert.Equal(t, tt.wantTime, !info.Created.IsZero())
+			}
+		})
+	}
+}
+
+func TestSendArgs(t *testing.T) {
+	const target = "tank/data@zrb_level1_2024-01-02_00-00"
+	const parent = "tank/data@zrb_level0_2024-01-01_00-00"
+	enabled, disabled := true, false
+
+	tests := []struct {
+		name   string
+		opts   SendOptions
+		parent string
+		dryRun bool
+		want   []string
+	}{
+		{name: "default full send", want: []string{"send", "-L", target}},
+		{name: "large blocks", opts: SendOptions{LargeBlocks: &enabled}, want: []string{"send", "-L", target}},
+		{name: "no large blocks", opts: SendOptions{LargeBlocks: &disabled}, want: []string{"send", target}},
+		{name: "embedded", opts: SendOptions{LargeBlocks: &disabled, Embedded: true}, want: []string{"send", "-e", target}},
+		{name: "compressed", opts: SendOptions{LargeBlocks: &disabled, Compressed: true}, want: []string{"send", "-c", target}},
+		{name: "raw", opts: SendOptions{LargeBlocks: &disabled, Raw: true}, want: []string{"send", "-w", target}},
+		{name: "properties", opts: SendOptions{LargeBlocks: &disabled, Properties: true}, want: []string{"send", "-p", target}},
+		{
+			name:   "incremental dry run with all flags",
+			opts:   SendOptions{LargeBlocks: &enabled, Embedded: true, Compressed: true, Raw: true, Properties: true},
+			parent: parent,
+			dryRun: true,
+			want:   []string{"send", "-n", "-P", "-L", "-e", "-c", "-w", "-p", "-i", parent, target},
+		},
+		{
+			name:   "incremental from bookmark",
+			parent: BookmarkName(parent),
+			want:   []string{"send", "-L", "-i", "tank/data#zrb_level0_2024-01-01_00-00", target},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			assert.Equal(t, tt.want, sendArgs(tt.opts, target, tt.parent, tt.dryRun))
+		})
+	}
+}
+
+func TestParseSnapshotList(t *testing.T) {
+	t.Run("snapshots", func(t *testing.T) {
+		output := "tank/data@zrb_level0_2024-01-01_00-00\t1704067200\t1048576\n" +
+			"tank/data@auto-2024-01-02\t1704153600\t0\n"
+
+		snapshots, err := parseSnapshotList(output)
+		require.NoError(t, err)
+		assert.Equal(t, []SnapshotInfo{
+			{Name: "tank/data@zrb_level0_2024-01-01_00-00", Created: time.Unix(1704067200, 0), Used: 1048576},
+			{Name: "tank/data@auto-2024-01-02", Created: time.Unix(1704153600, 0), Used: 0},
+		}, snapshots)
+	})
+
+	t.Run("no snapshots", func(t *testing.T) {
+		snapshots, err := parseSnapshotList("")
+		require.NoError(t, err)
+		assert.Empty(t, snapshots)
+	})
+
+	t.Run("human readable sizes", func(t *testing.T) {
+		_, err := parseSnapshotList("tank/data@snap\t1704067200\t1.5M\n")
+		assert.ErrorContains(t, err, "invalid used size")
+	})
+
+	t.Run("missing columns", func(t *testing.T) {
+		_, err := parseSnapshotList("tank/data@snap\n")
+		assert.ErrorContains(t, err, "unexpected zfs list output")
+	})
+}
+
+func TestPartSuffix(t *testing.T) {
+	assert.Equal(t, "aaaaaa", PartSuffix(0))
+	assert.Equal(t, "aaaaab", PartSuffix(1))
+	assert.Equal(t, "aaaaaz", PartSuffix(25))
+	assert.Equal(t, "aaaaba", PartSuffix(26))
+	assert.Equal(t, "aaabaa", PartSuffix(26*26))
+}
+
+func TestBookmarkName(t *testing.T) {
+	assert.Equal(t, "tank/data#zrb_level0_2024-01-01_00-00", BookmarkName("tank/data@zrb_level0_2024-01-01_00-00"))
+	assert.Equal(t, "tank/a/b#snap", BookmarkName("tank/a/b@snap"))
+}
+
+func TestCountingWriter(t *testing.T) {
+	counter := &countingWriter{}
+	n, err := io.Copy(io.MultiWriter(io.Discard, counter), strings.NewReader(strings.Repeat("x", 10000)))
+	require.NoError(t, err)
+	assert.Equal(t, int64(10000), n)
+	assert.Equal(t, int64(10000), counter.n)
+}
@@ -81,7 +81,7 @@ func TestIncrementalBackup(t *testing.T) {
 	})
 
 	t.Run("SnapshotLevel1", func(t *testing.T) {
-		v.mustExecSudo(t, remoteBin+" snapshot --pool "+incPool+" --dataset "+incDataset+" --prefix zrb_level1")
+		v.mustExecSudo(t, remoteBin+" snapshot --pool "+incPool+" --dataset "+incDataset+" --level 1")
 
 		out := v.mustExecSudo(t, "zfs list -t snapshot -H -o name "+incPool+"/"+incDataset)
 		assert.Contains(t, out, "zrb_level1")